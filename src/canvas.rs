@@ -0,0 +1,434 @@
+use crate::metadata::Metadata;
+use crate::page;
+use nannou::lyon;
+use nannou::prelude::*;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use tiny_skia::{Color, FillRule, LineCap, Paint, PathBuilder, Pixmap, Stroke, Transform};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Caps {
+    Butt,
+    Round,
+    Square,
+}
+
+// an outline's weight and colour
+pub type Outline = (f32, Rgba);
+
+// the drawing operations shared by the window and offline renderers,
+// in nannou's coordinates with the origin at the centre and y pointing up
+pub trait Canvas {
+    fn background(&mut self, color: Rgba);
+    fn line(&mut self, start: Point2, end: Point2, weight: f32, caps: Caps, color: Rgba);
+    fn polyline(&mut self, points: &[Point2], weight: f32, color: Rgba);
+    fn polygon(&mut self, points: &[Point2], fill: Option<Rgba>, outline: Option<Outline>);
+    fn ellipse(
+        &mut self,
+        centre: Point2,
+        radius: f32,
+        fill: Option<Rgba>,
+        outline: Option<Outline>,
+    );
+    // fills the area inside an odd number of the closed contours, such as the outlines of text
+    fn shape(&mut self, contours: &[Vec<Point2>], fill: Rgba);
+}
+
+impl Canvas for Draw {
+    fn background(&mut self, color: Rgba) {
+        Draw::background(self).color(color);
+    }
+
+    fn line(&mut self, start: Point2, end: Point2, weight: f32, caps: Caps, color: Rgba) {
+        let line = Draw::line(self)
+            .start(start)
+            .end(end)
+            .weight(weight)
+            .color(color);
+        match caps {
+            Caps::Butt => line.caps_butt(),
+            Caps::Round => line.caps_round(),
+            Caps::Square => line.caps_square(),
+        };
+    }
+
+    fn polyline(&mut self, points: &[Point2], weight: f32, color: Rgba) {
+        Draw::polyline(self)
+            .weight(weight)
+            .color(color)
+            .points(points.iter().copied());
+    }
+
+    fn polygon(&mut self, points: &[Point2], fill: Option<Rgba>, outline: Option<Outline>) {
+        let mut polygon = Draw::polygon(self);
+        if fill.is_none() {
+            polygon = polygon.no_fill();
+        }
+        if let Some((weight, color)) = outline {
+            polygon = polygon.stroke_weight(weight).stroke_color(color);
+        }
+        let polygon = polygon.points(points.iter().copied());
+        if let Some(color) = fill {
+            polygon.color(color);
+        }
+    }
+
+    fn ellipse(
+        &mut self,
+        centre: Point2,
+        radius: f32,
+        fill: Option<Rgba>,
+        outline: Option<Outline>,
+    ) {
+        let ellipse = Draw::ellipse(self).xy(centre).radius(radius);
+        let ellipse = match fill {
+            Some(color) => ellipse.color(color),
+            None => ellipse.no_fill(),
+        };
+        if let Some((weight, color)) = outline {
+            ellipse.stroke_weight(weight).stroke_color(color);
+        }
+    }
+
+    fn shape(&mut self, contours: &[Vec<Point2>], fill: Rgba) {
+        let point = |pt: &Point2| lyon::math::point(pt.x, pt.y);
+        let mut builder = lyon::path::Path::builder();
+        for contour in contours {
+            let Some((first, rest)) = contour.split_first() else {
+                continue;
+            };
+            builder.begin(point(first));
+            for pt in rest {
+                builder.line_to(point(pt));
+            }
+            builder.end(true);
+        }
+        Draw::path(self)
+            .fill()
+            .color(fill)
+            .events(builder.build().iter());
+    }
+}
+
+// draws into a scaled and shifted region of another canvas, such as the space left by a margin
+pub struct Inset<'a> {
+    canvas: &'a mut dyn Canvas,
+    scale: f32,
+    offset: Vec2,
+    // the size of a tile which the background fills, rather than the whole canvas
+    tile: Option<Vec2>,
+}
+
+impl<'a> Inset<'a> {
+    pub fn new(canvas: &'a mut dyn Canvas, scale: f32, offset: Vec2) -> Self {
+        Inset {
+            canvas,
+            scale,
+            offset,
+            tile: None,
+        }
+    }
+
+    // a rectangle `size` points across, one of several sharing the canvas
+    pub fn tile(canvas: &'a mut dyn Canvas, size: Vec2, scale: f32, offset: Vec2) -> Self {
+        Inset {
+            tile: Some(size),
+            ..Inset::new(canvas, scale, offset)
+        }
+    }
+
+    fn map(&self, pt: Point2) -> Point2 {
+        pt * self.scale + self.offset
+    }
+
+    fn map_all(&self, points: &[Point2]) -> Vec<Point2> {
+        points.iter().map(|pt| self.map(*pt)).collect()
+    }
+
+    fn outline(&self, outline: Option<Outline>) -> Option<Outline> {
+        outline.map(|(weight, color)| (weight * self.scale, color))
+    }
+}
+
+impl Canvas for Inset<'_> {
+    fn background(&mut self, color: Rgba) {
+        let Some(size) = self.tile else {
+            return self.canvas.background(color);
+        };
+        let half = size / 2.0;
+        let corners = [
+            pt2(-half.x, -half.y),
+            pt2(half.x, -half.y),
+            pt2(half.x, half.y),
+            pt2(-half.x, half.y),
+        ];
+        self.canvas
+            .polygon(&self.map_all(&corners), Some(color), None);
+    }
+
+    fn line(&mut self, start: Point2, end: Point2, weight: f32, caps: Caps, color: Rgba) {
+        let (start, end) = (self.map(start), self.map(end));
+        self.canvas
+            .line(start, end, weight * self.scale, caps, color);
+    }
+
+    fn polyline(&mut self, points: &[Point2], weight: f32, color: Rgba) {
+        let points = self.map_all(points);
+        self.canvas.polyline(&points, weight * self.scale, color);
+    }
+
+    fn polygon(&mut self, points: &[Point2], fill: Option<Rgba>, outline: Option<Outline>) {
+        let (points, outline) = (self.map_all(points), self.outline(outline));
+        self.canvas.polygon(&points, fill, outline);
+    }
+
+    fn ellipse(
+        &mut self,
+        centre: Point2,
+        radius: f32,
+        fill: Option<Rgba>,
+        outline: Option<Outline>,
+    ) {
+        let (centre, outline) = (self.map(centre), self.outline(outline));
+        self.canvas
+            .ellipse(centre, radius * self.scale, fill, outline);
+    }
+
+    fn shape(&mut self, contours: &[Vec<Point2>], fill: Rgba) {
+        let contours: Vec<_> = contours.iter().map(|c| self.map_all(c)).collect();
+        self.canvas.shape(&contours, fill);
+    }
+}
+
+// draws every stroke `width` points wide, as a plotter's pen does whatever weight it's given
+pub struct Pen<'a> {
+    canvas: &'a mut dyn Canvas,
+    width: f32,
+}
+
+impl<'a> Pen<'a> {
+    pub fn new(canvas: &'a mut dyn Canvas, width: f32) -> Self {
+        Pen { canvas, width }
+    }
+
+    fn outline(&self, outline: Option<Outline>) -> Option<Outline> {
+        outline.map(|(_, color)| (self.width, color))
+    }
+}
+
+impl Canvas for Pen<'_> {
+    fn background(&mut self, color: Rgba) {
+        self.canvas.background(color);
+    }
+
+    fn line(&mut self, start: Point2, end: Point2, _weight: f32, caps: Caps, color: Rgba) {
+        self.canvas.line(start, end, self.width, caps, color);
+    }
+
+    fn polyline(&mut self, points: &[Point2], _weight: f32, color: Rgba) {
+        self.canvas.polyline(points, self.width, color);
+    }
+
+    fn polygon(&mut self, points: &[Point2], fill: Option<Rgba>, outline: Option<Outline>) {
+        let outline = self.outline(outline);
+        self.canvas.polygon(points, fill, outline);
+    }
+
+    fn ellipse(
+        &mut self,
+        centre: Point2,
+        radius: f32,
+        fill: Option<Rgba>,
+        outline: Option<Outline>,
+    ) {
+        let outline = self.outline(outline);
+        self.canvas.ellipse(centre, radius, fill, outline);
+    }
+
+    fn shape(&mut self, contours: &[Vec<Point2>], fill: Rgba) {
+        self.canvas.shape(contours, fill);
+    }
+}
+
+// renders on the cpu, for exporting without a window
+pub struct Raster {
+    pixmap: Pixmap,
+    transform: Transform,
+    scale: f32,
+}
+
+impl Raster {
+    // an image of `width` by `height` points, at `scale` pixels per point
+    pub fn new(width: f32, height: f32, scale: f32) -> Result<Self, String> {
+        let pixels = |points: f32| (points * scale).round().max(1.0) as u32;
+        let (w, h) = (pixels(width), pixels(height));
+        let pixmap = Pixmap::new(w, h).ok_or_else(|| {
+            format!("a {w} by {h} pixel image is too large to render, try a lower `--dpi`")
+        })?;
+        Ok(Raster {
+            pixmap,
+            transform: Transform::from_row(scale, 0.0, 0.0, -scale, w as f32 / 2.0, h as f32 / 2.0),
+            scale,
+        })
+    }
+
+    // saves a png which records its resolution, so that it prints at the size of the page,
+    // and any metadata as text
+    pub fn save(&self, path: &Path, metadata: Option<&Metadata>) -> Result<(), String> {
+        let error = |e: png::EncodingError| format!("failed to write {}: {e}", path.display());
+        let file =
+            File::create(path).map_err(|e| format!("failed to create {}: {e}", path.display()))?;
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width(), self.height());
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let per_metre = (self.scale * page::INCH / 0.0254).round() as u32;
+        encoder.set_pixel_dims(Some(png::PixelDimensions {
+            xppu: per_metre,
+            yppu: per_metre,
+            unit: png::Unit::Meter,
+        }));
+        for (keyword, text) in metadata.map(Metadata::png_text).unwrap_or_default() {
+            encoder.add_text_chunk(keyword, text).map_err(error)?;
+        }
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.rgba()))
+            .map_err(error)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.pixmap.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.pixmap.height()
+    }
+
+    // the pixels as rows of non-premultiplied rgba bytes
+    pub fn rgba(&self) -> Vec<u8> {
+        self.pixmap
+            .pixels()
+            .iter()
+            .flat_map(|pixel| {
+                let color = pixel.demultiply();
+                [color.red(), color.green(), color.blue(), color.alpha()]
+            })
+            .collect()
+    }
+
+    fn color(color: Rgba) -> Color {
+        let clamp = |component: f32| component.clamp(0.0, 1.0);
+        Color::from_rgba(
+            clamp(color.red),
+            clamp(color.green),
+            clamp(color.blue),
+            clamp(color.alpha),
+        )
+        .unwrap()
+    }
+
+    fn paint(color: Rgba) -> Paint<'static> {
+        let mut paint = Paint::default();
+        paint.set_color(Self::color(color));
+        paint
+    }
+
+    fn fill(&mut self, path: &tiny_skia::Path, color: Rgba) {
+        let paint = Self::paint(color);
+        self.pixmap
+            .fill_path(path, &paint, FillRule::EvenOdd, self.transform, None);
+    }
+
+    fn stroke(&mut self, path: &tiny_skia::Path, weight: f32, caps: Caps, color: Rgba) {
+        let stroke = Stroke {
+            width: weight,
+            line_cap: match caps {
+                Caps::Butt => LineCap::Butt,
+                Caps::Round => LineCap::Round,
+                Caps::Square => LineCap::Square,
+            },
+            ..Stroke::default()
+        };
+        let paint = Self::paint(color);
+        self.pixmap
+            .stroke_path(path, &paint, &stroke, self.transform, None);
+    }
+}
+
+fn path(points: &[Point2], close: bool) -> Option<tiny_skia::Path> {
+    let mut builder = PathBuilder::new();
+    add_contour(&mut builder, points, close);
+    builder.finish()
+}
+
+fn add_contour(builder: &mut PathBuilder, points: &[Point2], close: bool) {
+    let Some((first, rest)) = points.split_first() else {
+        return;
+    };
+    builder.move_to(first.x, first.y);
+    for pt in rest {
+        builder.line_to(pt.x, pt.y);
+    }
+    if close {
+        builder.close();
+    }
+}
+
+impl Canvas for Raster {
+    fn background(&mut self, color: Rgba) {
+        self.pixmap.fill(Self::color(color));
+    }
+
+    fn line(&mut self, start: Point2, end: Point2, weight: f32, caps: Caps, color: Rgba) {
+        if let Some(path) = path(&[start, end], false) {
+            self.stroke(&path, weight, caps, color);
+        }
+    }
+
+    fn polyline(&mut self, points: &[Point2], weight: f32, color: Rgba) {
+        if let Some(path) = path(points, false) {
+            self.stroke(&path, weight, Caps::Butt, color);
+        }
+    }
+
+    fn polygon(&mut self, points: &[Point2], fill: Option<Rgba>, outline: Option<Outline>) {
+        let Some(path) = path(points, true) else {
+            return;
+        };
+        if let Some(color) = fill {
+            self.fill(&path, color);
+        }
+        if let Some((weight, color)) = outline {
+            self.stroke(&path, weight, Caps::Butt, color);
+        }
+    }
+
+    fn ellipse(
+        &mut self,
+        centre: Point2,
+        radius: f32,
+        fill: Option<Rgba>,
+        outline: Option<Outline>,
+    ) {
+        let Some(path) = PathBuilder::from_circle(centre.x, centre.y, radius) else {
+            return;
+        };
+        if let Some(color) = fill {
+            self.fill(&path, color);
+        }
+        if let Some((weight, color)) = outline {
+            self.stroke(&path, weight, Caps::Butt, color);
+        }
+    }
+
+    fn shape(&mut self, contours: &[Vec<Point2>], fill: Rgba) {
+        let mut builder = PathBuilder::new();
+        for contour in contours {
+            add_contour(&mut builder, contour, true);
+        }
+        if let Some(path) = builder.finish() {
+            self.fill(&path, fill);
+        }
+    }
+}
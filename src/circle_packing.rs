@@ -1,56 +1,108 @@
+use crate::canvas::Canvas;
 use crate::common;
+use crate::palette;
+use crate::seed;
+use crate::sketch::{Param, Sketch};
+use crate::tween::{self, Tween, Tweened};
 use nannou::prelude::*;
 use rand::Rng;
 
-struct Circle(Point2, f32);
-type Model = Vec<Circle>;
+#[derive(Clone)]
+pub struct Circle(Point2, f32);
+type Circles = Vec<Circle>;
+
+pub struct Model {
+    circles: Tweened<Circle>,
+    size: Vec2,
+}
 
 impl Circle {
     fn intersects_circle(&self, other: &Circle) -> bool {
         (self.0 - other.0).length() < self.1 + other.1
     }
-    fn intersects_square(&self, sq_size: f32) -> bool {
+    fn intersects_edge(&self, extent: Vec2) -> bool {
         let mut ret = false;
-        ret = ret || self.0.x + self.1 > sq_size / 2.0;
-        ret = ret || self.0.y + self.1 > sq_size / 2.0;
-        ret = ret || self.0.x - self.1 < -sq_size / 2.0;
-        ret = ret || self.0.y - self.1 < -sq_size / 2.0;
+        ret = ret || self.0.x + self.1 > extent.x / 2.0;
+        ret = ret || self.0.y + self.1 > extent.y / 2.0;
+        ret = ret || self.0.x - self.1 < -extent.x / 2.0;
+        ret = ret || self.0.y - self.1 < -extent.y / 2.0;
         ret
     }
 }
 
-pub fn run() {
-    nannou::app(model)
-        .event(|a, m, e| common::refresh_model_on_space(a, m, e, model))
-        .simple_window(view)
-        .size(800, 800)
-        .run();
+impl Tween for Circle {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Circle(self.0.lerp(other.0, t), tween::mix(self.1, other.1, t))
+    }
+
+    fn collapsed(&self) -> Self {
+        Circle(self.0, 0.0)
+    }
+}
+
+pub struct CirclePacking;
+
+impl Sketch for CirclePacking {
+    type Model = Model;
+
+    const NAME: &'static str = "circle-packing";
+    const DESCRIPTION: &'static str = "circles grown until they touch, in random places";
+
+    fn params() -> Vec<Param> {
+        tween::PARAMS.to_vec()
+    }
+
+    fn generate(size: Vec2) -> Self::Model {
+        Model {
+            circles: Tweened::new(generate_circles(size)),
+            size,
+        }
+    }
+
+    fn draw(model: &Self::Model, canvas: &mut dyn Canvas) {
+        draw_model(&model.circles.current(), model.size, canvas);
+    }
+
+    fn update(model: &mut Self::Model, seconds: f32) {
+        model.circles.advance(seconds);
+    }
+
+    fn regenerate(model: &mut Self::Model, size: Vec2) {
+        model.circles.retarget(generate_circles(size));
+        model.size = size;
+    }
+
+    // the same circles, scaled to fit the resized window
+    fn resize(model: &mut Self::Model, size: Vec2) {
+        let factor = common::fit(model.size, size);
+        model
+            .circles
+            .map(|circle| Circle(circle.0 * factor, circle.1 * factor));
+        model.size = size;
+    }
 }
 
-fn draw_circle(circle: &Circle, draw: &Draw) {
-    draw.ellipse()
-        .radius(circle.1)
-        .xy(circle.0)
-        .stroke_weight(2.0f32)
-        .rgba(0.0, 0.0, 0.0, 0.0);
+fn draw_circle(circle: &Circle, weight: f32, color: Rgba, canvas: &mut dyn Canvas) {
+    canvas.ellipse(circle.0, circle.1, None, Some((weight, color)));
 }
 
-fn model(app: &App) -> Model {
-    let size = app.main_window().inner_size_points().0 * 0.9;
+fn generate_circles(size: Vec2) -> Circles {
+    let (start_radius, growth) = (common::scaled(3.0, size), common::scaled(0.2, size));
+    let extent = size * 0.9;
     let n = 500_usize;
     let mut circles_created = 0_usize;
     let mut model = Vec::<Circle>::new();
-    let mut rng = rand::thread_rng();
+    let mut rng = seed::rng();
     loop {
         if circles_created == n {
             break;
         }
         let mut new_circle = Circle(
             pt2(
-                rng.gen_range(-0.5..0.5) * size,
-                rng.gen_range(-0.5..0.5) * size,
+                rng.gen_range(-0.5..0.5) * extent.x,
+                rng.gen_range(-0.5..0.5) * extent.y,
             ),
-            3.0,
+            start_radius,
         );
         if model
             .iter()
@@ -59,15 +111,15 @@ fn model(app: &App) -> Model {
             continue;
         }
         loop {
-            let max_radius = new_circle.1 > size * 0.25;
-            let intersects_edge = new_circle.intersects_square(size);
+            let max_radius = new_circle.1 > extent.min_element() * 0.25;
+            let intersects_edge = new_circle.intersects_edge(extent);
             let intersects_another_circle = model
                 .iter()
                 .any(|circle| circle.intersects_circle(&new_circle));
             if max_radius || intersects_edge || intersects_another_circle {
                 break;
             }
-            new_circle.1 += 0.2;
+            new_circle.1 += growth;
         }
         model.push(new_circle);
         circles_created += 1;
@@ -75,11 +127,11 @@ fn model(app: &App) -> Model {
     model
 }
 
-fn view(app: &App, model: &Model, frame: Frame) {
-    let draw = app.draw();
-    draw.background().color(WHITE);
-    for circle in model {
-        draw_circle(circle, &draw);
+fn draw_model(circles: &Circles, size: Vec2, canvas: &mut dyn Canvas) {
+    let palette = palette::current();
+    let weight = common::scaled(2.0, size);
+    canvas.background(palette.background);
+    for circle in circles {
+        draw_circle(circle, weight, palette.foreground, canvas);
     }
-    draw.to_frame(app, &frame).unwrap();
 }
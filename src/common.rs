@@ -1,19 +1,53 @@
+use crate::page;
 use nannou::prelude::*;
 
-pub fn refresh_model_on_space<M, G: FnOnce(&App) -> M>(
-    app: &App,
-    model: &mut M,
-    event: Event,
-    gen_model: G,
-) {
-    if let Event::WindowEvent {
-        id: _,
-        simple: Some(window_event),
-    } = event
-    {
-        if let WindowEvent::KeyReleased(Key::Space) = window_event {
-            *model = gen_model(app);
+pub fn window_size(app: &App) -> Vec2 {
+    app.main_window().inner_size_points().into()
+}
+
+// the size of the artwork in the window, inside the page's margin
+pub fn artwork_size(app: &App) -> Vec2 {
+    page::artwork(window_size(app))
+}
+
+// the window's starting size, that of the page
+pub fn page_size() -> (u32, u32) {
+    let size = page::current().size.round();
+    (size.x as u32, size.y as u32)
+}
+
+// the tutorials were first drawn on 800 point square windows, so lengths chosen there, such as
+// stroke weights, are scaled by this to keep their proportion on a canvas `size` across
+pub fn scaled(length: f32, size: Vec2) -> f32 {
+    length * size.min_element() / 800.0
+}
+
+// how much a piece `from` points across is scaled to fit within `to`, keeping its proportions
+pub fn fit(from: Vec2, to: Vec2) -> f32 {
+    (to / from).min_element()
+}
+
+pub fn resized(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::WindowEvent {
+            id: _,
+            simple: Some(WindowEvent::Resized(_)),
         }
+    )
+}
+
+pub fn space_released(event: &Event) -> bool {
+    key_released(event) == Some(Key::Space)
+}
+
+pub fn key_released(event: &Event) -> Option<Key> {
+    match event {
+        Event::WindowEvent {
+            id: _,
+            simple: Some(WindowEvent::KeyReleased(key)),
+        } => Some(*key),
+        _ => None,
     }
 }
 
@@ -1,67 +1,260 @@
+use crate::canvas::Canvas;
 use crate::common;
-use itertools::Itertools;
+use crate::label;
+use crate::layout::{self, Cell};
+use crate::palette;
+use crate::params;
+use crate::seed;
+use crate::sketch::{Param, Sketch};
+use crate::tween::{self, Tween, Tweened};
 use nannou::prelude::*;
 use rand::Rng;
 
-type Square = [Point2; 4];
+mod field;
 
-pub fn run() {
-    nannou::app(generate_squares)
-        .event(|a, m, e| common::refresh_model_on_space(a, m, e, generate_squares))
-        .simple_window(view)
-        .size(800, 800)
-        .run();
+const N: usize = 9;
+
+// a square's place and its random share of the disorder, which stay fixed
+// while the mouse changes how much disorder there is
+#[derive(Clone)]
+pub struct Square {
+    cell: Cell,
+    // rotation and displacement in x and y, in [-1, 1] scaled by their strengths
+    offsets: Vec3,
+    variance: f32,
+}
+
+// how squares near the cursor respond to it
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Mouse {
+    Off,
+    Disorder,
+    Calm,
+}
+
+const PARAMS: &[Param] = &[
+    Param {
+        name: "rotation",
+        default: "1",
+        values: "0 or more",
+        description: "the strength of the squares' rotation",
+        parse: |value| params::check_range(value, 0.0_f32..=f32::MAX),
+    },
+    Param {
+        name: "x_shift",
+        default: "1",
+        values: "0 or more",
+        description: "the strength of the squares' sideways displacement",
+        parse: |value| params::check_range(value, 0.0_f32..=f32::MAX),
+    },
+    Param {
+        name: "y_shift",
+        default: "0",
+        values: "0 or more",
+        description: "the strength of the squares' vertical displacement",
+        parse: |value| params::check_range(value, 0.0_f32..=f32::MAX),
+    },
+    Param {
+        name: "mouse",
+        default: "off",
+        values: "off, disorder, calm",
+        description: "how squares near the cursor respond to it",
+        parse: params::check::<Mouse>,
+    },
+    Param {
+        name: "reach",
+        default: "0.3",
+        values: "a fraction of the canvas",
+        description: "how far the cursor reaches",
+        parse: |value| params::check_range(value, 0.0_f32..=f32::MAX),
+    },
+];
+
+pub struct Model {
+    squares: Tweened<Square>,
+    size: Vec2,
+    // where the cursor is on the artwork, once it has moved over the window
+    cursor: Option<Point2>,
+    // where the disorder was moved to by a click, as a fraction of the grid
+    point: Option<Vec2>,
 }
 
-fn draw_square(square: &Square, draw: &Draw) {
-    draw.quad()
-        .points(square[0], square[1], square[2], square[3])
-        .stroke_weight(2.0f32)
-        .rgba(0.0, 0.0, 0.0, 0.0);
+pub struct CubicDisarray;
+
+impl Sketch for CubicDisarray {
+    type Model = Model;
+
+    const NAME: &'static str = "cubic-disarray";
+    const DESCRIPTION: &'static str = "a grid of squares falling into disorder";
+
+    fn params() -> Vec<Param> {
+        [PARAMS, field::PARAMS, layout::PARAMS, tween::PARAMS].concat()
+    }
+
+    fn generate(size: Vec2) -> Self::Model {
+        Model {
+            squares: Tweened::new(generate_squares(size, None)),
+            size,
+            cursor: None,
+            point: None,
+        }
+    }
+
+    fn draw(model: &Self::Model, canvas: &mut dyn Canvas) {
+        draw_model(model, canvas);
+    }
+
+    fn update(model: &mut Self::Model, seconds: f32) {
+        model.squares.advance(seconds);
+    }
+
+    fn regenerate(model: &mut Self::Model, size: Vec2) {
+        model.squares.retarget(generate_squares(size, model.point));
+        model.size = size;
+    }
+
+    // the same squares, scaled to fit the resized window, so that any image isn't read again
+    fn resize(model: &mut Self::Model, size: Vec2) {
+        let factor = common::fit(model.size, size);
+        model.squares.map(|square| Square {
+            cell: square.cell.scaled(factor),
+            ..square.clone()
+        });
+        model.size = size;
+    }
+
+    // clicking moves the disorder to a point under the cursor
+    fn event(app: &App, model: &mut Self::Model, event: &Event) {
+        let Event::WindowEvent {
+            id: _,
+            simple: Some(event),
+        } = event
+        else {
+            return;
+        };
+        match event {
+            WindowEvent::MouseMoved(position) => {
+                model.cursor = Some(label::unframe(*position, model.size));
+            }
+            WindowEvent::MousePressed(MouseButton::Left) => {
+                let cursor = label::unframe(app.mouse.position(), model.size);
+                let (start, extent) = draw_area(model.size);
+                // the same mapping as `Cell::fraction`, from the centre of the cell under the cursor
+                let step = Vec2::splat(extent.min_element() / N as f32);
+                model.point = Some((cursor - start - step / 2.0) / (extent - step));
+                // the same squares, with their disorder in a different place
+                seed::set(seed::current());
+                model
+                    .squares
+                    .retarget(generate_squares(model.size, model.point));
+            }
+            _ => {}
+        }
+    }
 }
 
-fn generate_squares(app: &App) -> Vec<Square> {
-    let n = 9_usize;
-    let (start, step) = {
-        let width = app.main_window().inner_size_points().0;
-        let draw_area = 0.9;
-        (-draw_area * width / 2.0, width * draw_area / n as f32)
-    };
-
-    let mut squares = Vec::new();
-    let mut rng = rand::thread_rng();
-    for (i, j) in (0..n).cartesian_product(0..n) {
-        let i = i as f32;
-        let j = j as f32;
-        let n = n as f32;
-        let mut square = [
+impl Square {
+    fn points(&self, variance: f32) -> [Point2; 4] {
+        let step = self.cell.size;
+        let mut points = [
             vec2(-step / 2.0f32, -step / 2.0f32),
             vec2(step / 2.0f32, -step / 2.0f32),
             vec2(step / 2.0f32, step / 2.0f32),
             vec2(-step / 2.0f32, step / 2.0f32),
         ];
-        let variance = 1.0 - j / (n - 1.0);
-        let rotate = rng.gen_range(-1.0f32..1.0f32) * variance * std::f32::consts::PI * 0.06;
-        let displacement = vec2(start + (i + 0.5) * step, start + (j + 0.5) * step)
-            + vec2(
-                rng.gen_range(-1.0f32..1.0f32) * variance * step / 2.0f32,
-                0.0,
-            );
-        for point in &mut square {
+        let rotate = self.offsets.x * variance * std::f32::consts::PI * 0.06;
+        let displacement =
+            self.cell.centre() + vec2(self.offsets.y, self.offsets.z) * variance * step / 2.0f32;
+        for point in &mut points {
             *point = point.rotate(rotate);
             *point += displacement;
         }
-        squares.push(square);
+        points
     }
+}
+
+impl Tween for Square {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Square {
+            cell: self.cell.lerp(&other.cell, t),
+            offsets: self.offsets.lerp(other.offsets, t),
+            variance: tween::mix(self.variance, other.variance, t),
+        }
+    }
+
+    fn collapsed(&self) -> Self {
+        Square {
+            cell: self.cell.collapsed(),
+            ..self.clone()
+        }
+    }
+}
+
+impl std::str::FromStr for Mouse {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Mouse::Off),
+            "disorder" => Ok(Mouse::Disorder),
+            "calm" => Ok(Mouse::Calm),
+            _ => Err("expected one of off, disorder, calm".to_owned()),
+        }
+    }
+}
+
+fn draw_square(square: &Square, variance: f32, weight: f32, color: Rgba, canvas: &mut dyn Canvas) {
+    canvas.polygon(
+        &square.points(variance),
+        None,
+        Some((weight * square.cell.scale, color)),
+    );
+}
+
+fn draw_area(size: Vec2) -> (Point2, Vec2) {
+    let draw_area = 0.9;
+    (-draw_area * size / 2.0, size * draw_area)
+}
+
+fn generate_squares(size: Vec2, point: Option<Vec2>) -> Vec<Square> {
+    let (start, extent) = draw_area(size);
+    let variance = field::Variance::from_params(point);
+    let rotation = params::get("rotation", 1.0_f32);
+    let x_shift = params::get("x_shift", 1.0_f32);
+    let y_shift = params::get("y_shift", 0.0_f32);
 
-    squares
+    let mut rng = seed::rng();
+    layout::cells(start, extent, N)
+        .into_iter()
+        .map(|cell| Square {
+            cell,
+            offsets: vec3(rotation, x_shift, y_shift)
+                * vec3(
+                    rng.gen_range(-1.0f32..1.0f32),
+                    rng.gen_range(-1.0f32..1.0f32),
+                    rng.gen_range(-1.0f32..1.0f32),
+                ),
+            variance: variance.at(cell.fraction(start, extent)),
+        })
+        .collect()
 }
 
-fn view(app: &App, model: &Vec<Square>, frame: Frame) {
-    let draw = app.draw();
-    draw.background().color(WHITE);
-    for square in model {
-        draw_square(square, &draw);
+// squares within `reach` of the cursor, as a fraction of the canvas, follow the `mouse` mode
+fn draw_model(model: &Model, canvas: &mut dyn Canvas) {
+    let mouse = params::get("mouse", Mouse::Off);
+    let reach = params::get("reach", 0.3_f32).max(field::MIN_REACH) * model.size.min_element();
+    let palette = palette::current();
+    let weight = common::scaled(2.0, model.size);
+    canvas.background(palette.background);
+    for square in model.squares.current() {
+        let closeness = model.cursor.map_or(0.0, |cursor| {
+            (1.0 - square.cell.centre().distance(cursor) / reach).clamp(0.0, 1.0)
+        });
+        let variance = match mouse {
+            Mouse::Off => square.variance,
+            Mouse::Disorder => tween::mix(square.variance, 1.0, closeness),
+            Mouse::Calm => tween::mix(square.variance, 0.0, closeness),
+        };
+        draw_square(&square, variance, weight, palette.foreground, canvas);
     }
-    draw.to_frame(app, &frame).unwrap();
 }
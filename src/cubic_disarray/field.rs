@@ -0,0 +1,192 @@
+use crate::params;
+use crate::seed;
+use crate::sketch::Param;
+use nannou::image::{self, GrayImage};
+use nannou::noise::{NoiseFn, OpenSimplex, Seedable};
+use nannou::prelude::*;
+use rand::Rng;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+// where the disorder lies, over positions in the unit square with y pointing up
+#[derive(Clone, Debug, PartialEq)]
+pub enum Field {
+    // strongest at the bottom, as in the tutorial
+    Linear,
+    // strongest at the corners, growing away from the centre
+    Radial,
+    // strongest at a point, fading out over `falloff`
+    Point(Vec2),
+    Noise,
+    // strongest where the image is brightest
+    Image(PathBuf),
+}
+
+// the shortest falloff or reach, as a fraction of the canvas, so that neither is ever zero
+pub const MIN_REACH: f32 = 0.001;
+
+// the last image opened for an image field, so it is read once however often the field is made,
+// starting when the `field` parameter is checked
+static IMAGE: RwLock<Option<(PathBuf, GrayImage)>> = RwLock::new(None);
+
+// the amount of disorder in the range [0, 1] across the canvas, from the cubic disarray parameters
+pub struct Variance {
+    field: Field,
+    falloff: f32,
+    frequency: f64,
+    invert: bool,
+    simplex: OpenSimplex,
+    image: Option<GrayImage>,
+}
+
+// the parameters of `Variance`
+pub const PARAMS: &[Param] = &[
+    Param {
+        name: "field",
+        default: "linear",
+        values: "linear, radial, point, point:<x>,<y>, noise, image:<file>",
+        description: "where the disorder lies",
+        parse: check,
+    },
+    Param {
+        name: "falloff",
+        default: "0.5",
+        values: "a fraction of the canvas",
+        description: "how far the disorder reaches from a point",
+        parse: |value| params::check_range(value, 0.0_f32..=f32::MAX),
+    },
+    Param {
+        name: "frequency",
+        default: "3",
+        values: "cycles across the canvas",
+        description: "the scale of the noise",
+        parse: |value| params::check_range(value, 0.0..=f64::MAX),
+    },
+    Param {
+        name: "invert",
+        default: "false",
+        values: "true, false",
+        description: "whether to swap the ordered and disordered parts",
+        parse: params::check::<bool>,
+    },
+];
+
+impl std::str::FromStr for Field {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(Field::Linear),
+            "radial" => Ok(Field::Radial),
+            "point" => Ok(Field::Point(vec2(0.5, 0.5))),
+            "noise" => Ok(Field::Noise),
+            _ => {
+                if let Some(path) = s.strip_prefix("image:") {
+                    return Ok(Field::Image(PathBuf::from(path)));
+                }
+                let point = s.strip_prefix("point:").ok_or(
+                    "expected one of linear, radial, point, point:<x>,<y>, noise, image:<file>",
+                )?;
+                let (x, y) = point
+                    .split_once(',')
+                    .ok_or_else(|| format!("expected `point:<x>,<y>`, found `{s}`"))?;
+                let coord = |c: &str| c.trim().parse::<f32>().map_err(|e| format!("{e}"));
+                Ok(Field::Point(vec2(coord(x)?, coord(y)?)))
+            }
+        }
+    }
+}
+
+impl Variance {
+    // with the disorder at `point` when one has been clicked, rather than where `field` puts it
+    pub fn from_params(point: Option<Vec2>) -> Self {
+        let field = match point {
+            Some(point) => Field::Point(point),
+            None => params::get("field", Field::Linear),
+        };
+        let image = match &field {
+            // the image was opened when the parameter was checked
+            Field::Image(path) => Some(open(path).expect("the field's image was checked")),
+            _ => None,
+        };
+        Variance {
+            field,
+            falloff: params::get("falloff", 0.5_f32).max(MIN_REACH),
+            frequency: params::get("frequency", 3.0),
+            invert: params::get("invert", false),
+            simplex: OpenSimplex::new().set_seed(seed::rng().gen()),
+            image,
+        }
+    }
+
+    pub fn at(&self, position: Vec2) -> f32 {
+        let variance = match &self.field {
+            Field::Linear => 1.0 - position.y,
+            Field::Radial => (position - vec2(0.5, 0.5)).length() / 0.5.sqrt(),
+            Field::Point(point) => 1.0 - (position - *point).length() / self.falloff,
+            // open simplex noise lies roughly within [-0.5, 0.5]
+            Field::Noise => {
+                let [x, y] = [position.x, position.y].map(|c| c as f64 * self.frequency);
+                self.simplex.get([x, y]) as f32 + 0.5
+            }
+            Field::Image(_) => self
+                .image
+                .as_ref()
+                .map_or(0.0, |image| luminance(image, position)),
+        }
+        .clamp(0.0, 1.0);
+        if self.invert {
+            1.0 - variance
+        } else {
+            variance
+        }
+    }
+}
+
+// whether the field parses, and any image it names opens
+fn check(value: &str) -> Result<(), String> {
+    match value.parse()? {
+        Field::Image(path) => open(&path).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+fn open(path: &Path) -> Result<GrayImage, String> {
+    if let Some((opened, image)) = IMAGE.read().unwrap().as_ref() {
+        if opened == path {
+            return Ok(image.clone());
+        }
+    }
+    let image = image::open(path)
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))?
+        .to_luma8();
+    *IMAGE.write().unwrap() = Some((path.to_owned(), image.clone()));
+    Ok(image)
+}
+
+fn luminance(image: &GrayImage, position: Vec2) -> f32 {
+    let (width, height) = image.dimensions();
+    let pixel = |coord: f32, len: u32| (coord.clamp(0.0, 1.0) * (len - 1) as f32).round() as u32;
+    // image rows run downwards
+    let luma = image.get_pixel(pixel(position.x, width), pixel(1.0 - position.y, height));
+    luma.0[0] as f32 / 255.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_points_and_images() {
+        assert_eq!("point".parse(), Ok(Field::Point(vec2(0.5, 0.5))));
+        assert_eq!("point:0.25, 1".parse(), Ok(Field::Point(vec2(0.25, 1.0))));
+        assert_eq!(
+            "image:photo.png".parse(),
+            Ok(Field::Image(PathBuf::from("photo.png")))
+        );
+        assert!("point:0.25".parse::<Field>().is_err());
+        assert!("spiral".parse::<Field>().is_err());
+        assert!(check("image:missing.png").is_err());
+        assert_eq!(check("noise"), Ok(()));
+    }
+}
@@ -0,0 +1,343 @@
+use crate::palette::{self, Palette};
+use crate::params;
+use crate::seed;
+use crate::sketch::Param;
+use crate::tween;
+use nannou::prelude::*;
+use rand::Rng;
+
+// a colour in the oklab space, where equal steps look like equal changes:
+// lightness from zero to one, then green to red and blue to yellow
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Oklab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+}
+
+// how fills are chosen, from the `fill` parameter
+#[derive(Clone, Debug, PartialEq)]
+pub enum Scheme {
+    // no chroma, only lightness
+    Gray,
+    // the palette's accents
+    Accents,
+    // hues around the base `hue`
+    Monochrome,
+    Analogous,
+    Complementary,
+    Triadic,
+    Tetradic,
+    // an even gradient through anchor colours, by default the palette's accents
+    Gradient(Vec<Rgba>),
+}
+
+// fills in perceptually even steps, within a band of lightness set back from the background
+pub struct Fills {
+    scheme: Scheme,
+    // degrees
+    hue: f32,
+    chroma: f32,
+    lightness: (f32, f32),
+    accents: Vec<Rgba>,
+}
+
+// the parameters of tutorials with `Fills`
+pub const PARAMS: &[Param] = &[
+    Param {
+        name: "fill",
+        default: "the tutorial's own",
+        values: "gray, accents, monochrome, analogous, complementary, triadic, tetradic, gradient, gradient:<colour>,<colour>,...",
+        description: "the scheme the fills are chosen from",
+        parse: params::check::<Scheme>,
+    },
+    Param {
+        name: "hue",
+        default: "random",
+        values: "0 to 360",
+        description: "the base hue of a harmony in degrees",
+        parse: |value| params::check_range(value, 0.0_f32..=360.0),
+    },
+    Param {
+        name: "lightness",
+        default: "0.6, or 0.75 on a dark background",
+        values: "0 to 1",
+        description: "the middle of the fills' lightness",
+        parse: |value| params::check_range(value, 0.0_f32..=1.0),
+    },
+    Param {
+        name: "contrast",
+        default: "0.4",
+        values: "0 to 1",
+        description: "the spread of the fills' lightness",
+        parse: |value| params::check_range(value, 0.0_f32..=1.0),
+    },
+    Param {
+        name: "chroma",
+        default: "0.12",
+        values: "0 to 0.37",
+        description: "the intensity of a harmony's colours, reduced where they can't be shown",
+        parse: |value| params::check_range(value, 0.0_f32..=0.37),
+    },
+];
+
+impl std::str::FromStr for Scheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gray" => Ok(Scheme::Gray),
+            "accents" => Ok(Scheme::Accents),
+            "monochrome" => Ok(Scheme::Monochrome),
+            "analogous" => Ok(Scheme::Analogous),
+            "complementary" => Ok(Scheme::Complementary),
+            "triadic" => Ok(Scheme::Triadic),
+            "tetradic" => Ok(Scheme::Tetradic),
+            "gradient" => Ok(Scheme::Gradient(Vec::new())),
+            _ => {
+                let anchors = s.strip_prefix("gradient:").ok_or(
+                    "expected one of gray, accents, monochrome, analogous, complementary, \
+                     triadic, tetradic, gradient, gradient:<colour>,<colour>,...",
+                )?;
+                anchors
+                    .split(',')
+                    .map(|anchor| palette::parse_hex(anchor.trim()))
+                    .collect::<Result<_, _>>()
+                    .map(Scheme::Gradient)
+            }
+        }
+    }
+}
+
+impl Scheme {
+    // the harmony's hues as offsets in degrees from the base hue
+    fn hues(&self) -> &'static [f32] {
+        match self {
+            Scheme::Analogous => &[-30.0, 0.0, 30.0],
+            Scheme::Complementary => &[0.0, 180.0],
+            Scheme::Triadic => &[0.0, 120.0, 240.0],
+            Scheme::Tetradic => &[0.0, 90.0, 180.0, 270.0],
+            _ => &[0.0],
+        }
+    }
+}
+
+impl Fills {
+    // the tutorial's own colours are kept unless a `fill` is given
+    pub fn from_params() -> Option<Self> {
+        let scheme = params::try_get::<Scheme>("fill")?;
+        let palette = palette::current();
+        let hue = params::try_get("hue").unwrap_or_else(|| seed::rng().gen_range(0.0..360.0));
+        // fills sit away from the background, darker on light paper and lighter on dark
+        let default = if Oklab::from(palette.background).l > 0.5 {
+            0.6
+        } else {
+            0.75
+        };
+        let lightness = params::get("lightness", default);
+        let contrast = params::get("contrast", 0.4_f32).clamp(0.0, 1.0);
+        Some(Fills {
+            scheme,
+            hue,
+            chroma: params::get("chroma", 0.12),
+            lightness: (
+                (lightness - contrast / 2.0).clamp(0.0, 1.0),
+                (lightness + contrast / 2.0).clamp(0.0, 1.0),
+            ),
+            accents: Self::accents(&palette),
+        })
+    }
+
+    fn accents(palette: &Palette) -> Vec<Rgba> {
+        if palette.accents.is_empty() {
+            vec![palette.foreground]
+        } else {
+            palette.accents.clone()
+        }
+    }
+
+    // a fill part way along the scheme, from its darkest first hue to its lightest last one
+    pub fn at(&self, t: f32) -> Rgba {
+        let t = t.clamp(0.0, 1.0);
+        match &self.scheme {
+            Scheme::Accents => self.accents[(t * (self.accents.len() - 1) as f32).round() as usize],
+            Scheme::Gradient(anchors) => {
+                let anchors = if anchors.is_empty() {
+                    &self.accents
+                } else {
+                    anchors
+                };
+                gradient(anchors, t)
+            }
+            scheme => {
+                let hues = scheme.hues();
+                let hue = hues[(t * (hues.len() - 1) as f32).round() as usize];
+                self.color(tween::mix(self.lightness.0, self.lightness.1, t), hue)
+            }
+        }
+    }
+
+    // a random fill, with the scheme's hues and lightnesses drawn independently
+    pub fn sample(&self, rng: &mut impl Rng) -> Rgba {
+        match &self.scheme {
+            Scheme::Accents => self.accents[rng.gen_range(0..self.accents.len())],
+            Scheme::Gradient(_) => self.at(rng.gen_range(0.0..=1.0)),
+            scheme => {
+                let hues = scheme.hues();
+                let hue = hues[rng.gen_range(0..hues.len())];
+                let (min, max) = self.lightness;
+                self.color(tween::mix(min, max, rng.gen_range(0.0..=1.0)), hue)
+            }
+        }
+    }
+
+    fn color(&self, lightness: f32, offset: f32) -> Rgba {
+        let chroma = if self.scheme == Scheme::Gray {
+            0.0
+        } else {
+            self.chroma
+        };
+        oklch(lightness, chroma, self.hue + offset)
+    }
+}
+
+// evenly spaced stops through the anchors, mixed in oklab
+pub fn gradient(anchors: &[Rgba], t: f32) -> Rgba {
+    let Some(&last) = anchors.last() else {
+        return Rgba::new(0.0, 0.0, 0.0, 1.0);
+    };
+    let position = t.clamp(0.0, 1.0) * (anchors.len() - 1) as f32;
+    let index = position.floor() as usize;
+    if index + 1 >= anchors.len() {
+        return last;
+    }
+    let (from, to) = (Oklab::from(anchors[index]), Oklab::from(anchors[index + 1]));
+    let t = position - index as f32;
+    Oklab {
+        l: tween::mix(from.l, to.l, t),
+        a: tween::mix(from.a, to.a, t),
+        b: tween::mix(from.b, to.b, t),
+    }
+    .to_rgba()
+}
+
+// a colour from lightness, chroma and a hue in degrees, with as much of the chroma
+// as fits within srgb
+pub fn oklch(lightness: f32, chroma: f32, hue: f32) -> Rgba {
+    let hue = hue.to_radians();
+    let at = |chroma: f32| Oklab {
+        l: lightness,
+        a: chroma * hue.cos(),
+        b: chroma * hue.sin(),
+    };
+    if at(chroma).in_gamut() {
+        return at(chroma).to_rgba();
+    }
+    // bisects for the greatest chroma in gamut, which always includes the grays
+    let (mut low, mut high) = (0.0, chroma);
+    for _ in 0..16 {
+        let mid = (low + high) / 2.0;
+        if at(mid).in_gamut() {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    at(low).to_rgba()
+}
+
+fn to_linear(component: f32) -> f32 {
+    if component <= 0.04045 {
+        component / 12.92
+    } else {
+        ((component + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn from_linear(component: f32) -> f32 {
+    if component <= 0.0031308 {
+        component * 12.92
+    } else {
+        1.055 * component.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+impl From<Rgba> for Oklab {
+    fn from(color: Rgba) -> Self {
+        let [r, g, b] = [color.red, color.green, color.blue].map(to_linear);
+        let l = (0.41222147 * r + 0.53633254 * g + 0.05144599 * b).cbrt();
+        let m = (0.2119035 * r + 0.6806995 * g + 0.10739696 * b).cbrt();
+        let s = (0.08830246 * r + 0.28171884 * g + 0.6299787 * b).cbrt();
+        Oklab {
+            l: 0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+            a: 1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+            b: 0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+        }
+    }
+}
+
+impl Oklab {
+    fn linear(&self) -> [f32; 3] {
+        let l = (self.l + 0.39633778 * self.a + 0.21580376 * self.b).powi(3);
+        let m = (self.l - 0.105561346 * self.a - 0.06385417 * self.b).powi(3);
+        let s = (self.l - 0.08948418 * self.a - 1.2914855 * self.b).powi(3);
+        [
+            4.0767417 * l - 3.3077116 * m + 0.23096994 * s,
+            -1.268438 * l + 2.6097574 * m - 0.34131938 * s,
+            -0.0041960863 * l - 0.7034186 * m + 1.7076147 * s,
+        ]
+    }
+
+    fn in_gamut(&self) -> bool {
+        self.linear()
+            .iter()
+            .all(|component| (-1e-4..=1.0 + 1e-4).contains(component))
+    }
+
+    pub fn to_rgba(self) -> Rgba {
+        let [r, g, b] = self
+            .linear()
+            .map(|component| from_linear(component.clamp(0.0, 1.0)));
+        Rgba::new(r, g, b, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oklab_round_trips_and_stays_in_gamut() {
+        let white = Oklab::from(Rgba::new(1.0, 1.0, 1.0, 1.0));
+        assert!((white.l - 1.0).abs() < 1e-3 && white.a.abs() < 1e-3 && white.b.abs() < 1e-3);
+        let color = Rgba::new(0.8, 0.2, 0.4, 1.0);
+        let back = Oklab::from(color).to_rgba();
+        for (x, y) in [
+            (color.red, back.red),
+            (color.green, back.green),
+            (color.blue, back.blue),
+        ] {
+            assert!((x - y).abs() < 1e-3);
+        }
+        // far more chroma than srgb can show is cut back to fit
+        assert!(Oklab::from(oklch(0.7, 1.0, 30.0)).l > 0.69);
+    }
+
+    #[test]
+    fn parses_gradients() {
+        assert_eq!(
+            "gradient:#000000, ffffff".parse(),
+            Ok(Scheme::Gradient(vec![
+                Rgba::new(0.0, 0.0, 0.0, 1.0),
+                Rgba::new(1.0, 1.0, 1.0, 1.0)
+            ]))
+        );
+        let gray = gradient(
+            &[Rgba::new(0.0, 0.0, 0.0, 1.0), Rgba::new(1.0, 1.0, 1.0, 1.0)],
+            0.5,
+        );
+        // half way in lightness is darker than half way in srgb
+        assert!((Oklab::from(gray).l - 0.5).abs() < 1e-3 && gray.red < 0.5);
+        assert!("rainbow".parse::<Scheme>().is_err());
+    }
+}
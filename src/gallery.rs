@@ -0,0 +1,107 @@
+use crate::common;
+use crate::label;
+use crate::seed;
+use crate::sketch::{self, Entry, Instance};
+use nannou::prelude::*;
+use std::sync::RwLock;
+
+// how a gallery moves through its sketches
+pub struct Options {
+    pub sketches: Vec<&'static Entry>,
+    // how long each generation is shown for while not paused
+    pub seconds: f32,
+    // how many generations of each sketch are shown before moving on to the next
+    pub seeds: usize,
+}
+
+// nannou builds the model from a function pointer, so the options wait here
+static OPTIONS: RwLock<Option<Options>> = RwLock::new(None);
+
+struct Model {
+    options: Options,
+    index: usize,
+    instance: Box<dyn Instance>,
+    // generations of the current sketch shown so far
+    shown: usize,
+    elapsed: f32,
+    paused: bool,
+}
+
+// opens one window which cycles through the sketches, with a fresh seed for each generation:
+// Left and Right move between sketches, Space shows the next generation and P pauses
+pub fn run(options: Options) {
+    *OPTIONS.write().unwrap() = Some(options);
+    let (width, height) = common::page_size();
+    nannou::app(model)
+        .update(update)
+        .event(event)
+        .simple_window(view)
+        .size(width, height)
+        .run();
+}
+
+fn model(app: &App) -> Model {
+    let options = OPTIONS.write().unwrap().take().unwrap();
+    let sketch = options.sketches[0];
+    label::set_tutorial(sketch.name);
+    Model {
+        instance: (sketch.start)(common::artwork_size(app)),
+        options,
+        index: 0,
+        shown: 1,
+        elapsed: 0.0,
+        paused: false,
+    }
+}
+
+impl Model {
+    // moves `step` sketches along, or to the next generation of this one
+    fn show(&mut self, step: isize, size: Vec2) {
+        seed::next();
+        self.elapsed = 0.0;
+        if step == 0 {
+            self.shown += 1;
+            self.instance.regenerate(size);
+            return;
+        }
+        let len = self.options.sketches.len() as isize;
+        self.index = (self.index as isize + step).rem_euclid(len) as usize;
+        let sketch = self.options.sketches[self.index];
+        label::set_tutorial(sketch.name);
+        self.shown = 1;
+        self.instance = (sketch.start)(size);
+    }
+}
+
+fn update(app: &App, model: &mut Model, update: Update) {
+    let seconds = update.since_last.as_secs_f32();
+    model.instance.update(seconds);
+    if model.paused {
+        return;
+    }
+    model.elapsed += seconds;
+    if model.elapsed >= model.options.seconds {
+        let step = if model.shown < model.options.seeds {
+            0
+        } else {
+            1
+        };
+        model.show(step, common::artwork_size(app));
+    }
+}
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    sketch::event(app, model.instance.as_mut(), &event);
+    let size = common::artwork_size(app);
+    match common::key_released(&event) {
+        Some(Key::Right) => model.show(1, size),
+        Some(Key::Left) => model.show(-1, size),
+        Some(Key::Space) => model.show(0, size),
+        Some(Key::P) => model.paused = !model.paused,
+        _ => {}
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    sketch::view(app, model.instance.as_ref(), frame);
+}
@@ -1,6 +1,14 @@
+use crate::canvas::Canvas;
 use crate::common;
+use crate::palette;
+use crate::params;
+use crate::sketch::{Param, Sketch};
+use crate::text;
 use nannou::prelude::*;
 
+mod calendar;
+mod sun;
+
 type Polygon = Vec<Point2>;
 #[derive(Debug)]
 struct Rectangle(Point2, Point2);
@@ -11,6 +19,34 @@ struct Edge {
 }
 type LineSegment = (Point2, Point2);
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Layout {
+    // a square filled column by column
+    Grid,
+    // a row for each week, from monday to sunday
+    Weeks,
+    // a block of weeks for each month, three months to a row
+    Months,
+    // a ring for each month, from january in the middle
+    Radial,
+}
+
+// the days of a year in square cells, in units of a cell with y pointing down from the top left
+struct Calendar {
+    // the centre of each day's cell
+    days: Vec<Vec2>,
+    // the names of the months and the centres they are written at
+    labels: Vec<(&'static str, Vec2)>,
+    label_size: f32,
+    extent: Vec2,
+}
+
+pub struct Model {
+    lines: Vec<Polygon>,
+    // text, its centre and its size in points
+    labels: Vec<(&'static str, Point2, f32)>,
+}
+
 impl Edge {
     fn contains(&self, pt: Point2) -> bool {
         (pt - self.position).dot(self.inside) > 0.0
@@ -28,13 +64,13 @@ impl Edge {
 
         let determinant = b1 * (p2 - q2) - b2 * (p1 - q1);
 
-        if determinant.abs() < std::f32::EPSILON {
+        if determinant.abs() < f32::EPSILON {
             return None;
         }
 
         let t = (b1 * (a2 - q2) - b2 * (a1 - q1)) / determinant;
 
-        if t < 0.0 || t > 1.0 {
+        if !(0.0..=1.0).contains(&t) {
             return None;
         }
 
@@ -115,65 +151,223 @@ fn clip(mut poly: Polygon, mask: &Rectangle) -> Polygon {
     poly
 }
 
-pub fn run() {
-    nannou::app(model)
-        .simple_window(view)
-        .size(800, 800)
-        .run();
-}
-
-fn model(app: &App) -> Vec<Polygon> {
-    let n = 365.0.sqrt().ceil() as usize;
-    let size = app.main_window().inner_size_points().0 * 0.9;
-    let start = -size / 2.0;
-    let step = size / n as f32;
-    let mut count = 0;
-    let mut row = 0;
-    let mut ret = Vec::new();
-    loop {
-        if count == 365 {
-            break;
+impl std::str::FromStr for Layout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "grid" => Ok(Layout::Grid),
+            "weeks" => Ok(Layout::Weeks),
+            "months" => Ok(Layout::Months),
+            "radial" => Ok(Layout::Radial),
+            _ => Err("expected one of grid, weeks, months, radial".to_owned()),
+        }
+    }
+}
+
+// columns of days, with as many rows as keep the grid to the shape of `size`
+fn grid(year: i32, size: Vec2) -> Calendar {
+    let days = calendar::days_in_year(year);
+    let n = (days as f32 * size.y / size.x).sqrt().ceil() as usize;
+    let columns = days.div_ceil(n);
+    Calendar {
+        days: (0..days)
+            .map(|day| vec2((day / n) as f32 + 0.5, (day % n) as f32 + 0.5))
+            .collect(),
+        labels: Vec::new(),
+        label_size: 0.0,
+        extent: vec2(columns as f32, n as f32),
+    }
+}
+
+fn weeks(year: i32, labels: bool) -> Calendar {
+    // a column to the left of the weeks for the months' names
+    let margin = if labels { 2.0 } else { 0.0 };
+    let first = calendar::first_weekday(year);
+    let days = calendar::days_in_year(year);
+    let cell = |day: usize| {
+        let index = first + day;
+        vec2(margin + (index % 7) as f32 + 0.5, (index / 7) as f32 + 0.5)
+    };
+    Calendar {
+        days: (0..days).map(cell).collect(),
+        labels: if labels {
+            calendar::months(year)
+                .iter()
+                .zip(calendar::MONTHS)
+                .map(|(&(start, _), name)| (name, vec2(margin / 2.0, cell(start).y)))
+                .collect()
+        } else {
+            Vec::new()
+        },
+        label_size: 0.8,
+        extent: vec2(margin + 7.0, (first + days).div_ceil(7) as f32),
+    }
+}
+
+fn months(year: i32, labels: bool) -> Calendar {
+    // a row above each month for its name
+    let header = if labels { 1.0 } else { 0.0 };
+    // seven days by six weeks, and a gap of a cell
+    let block = vec2(8.0, 7.0 + header);
+    let weekday = calendar::first_weekday(year);
+    let mut ret = Calendar {
+        days: Vec::new(),
+        labels: Vec::new(),
+        label_size: 0.8,
+        extent: vec2(3.0, 4.0) * block - vec2(1.0, 1.0),
+    };
+    for (month, (start, length)) in calendar::months(year).into_iter().enumerate() {
+        let origin = vec2((month % 3) as f32, (month / 3) as f32) * block;
+        let offset = (weekday + start) % 7;
+        ret.days.extend((offset..offset + length).map(|index| {
+            origin + vec2((index % 7) as f32 + 0.5, header + (index / 7) as f32 + 0.5)
+        }));
+        if labels {
+            ret.labels
+                .push((calendar::MONTHS[month], origin + vec2(3.5, 0.5)));
         }
-        if count % n == 0 {
-            row += 1;
+    }
+    ret
+}
+
+fn radial(year: i32, labels: bool) -> Calendar {
+    // a slot for each day of the longest month and one for its name,
+    // spaced a cell apart around the innermost ring
+    let slots = 32;
+    let inner = slots as f32 / TAU;
+    let outer = inner + 11.0;
+    let centre = vec2(outer + 0.5, outer + 0.5);
+    let at = |ring: usize, slot: usize| {
+        let angle = TAU * slot as f32 / slots as f32;
+        centre + vec2(angle.sin(), -angle.cos()) * (inner + ring as f32)
+    };
+    let mut ret = Calendar {
+        days: Vec::new(),
+        labels: Vec::new(),
+        label_size: 0.45,
+        extent: centre * 2.0,
+    };
+    for (month, (_, length)) in calendar::months(year).into_iter().enumerate() {
+        ret.days.extend((1..=length).map(|slot| at(month, slot)));
+        if labels {
+            ret.labels.push((calendar::MONTHS[month], at(month, 0)));
         }
-        let col = count % n;
-        let bottom_left = pt2(start + row as f32 * step, -start - col as f32 * step) - pt2(step, step) * 0.5;
-        let phi = count as f32 / 365.0 * PI;
-        let angle = phi.sin() * PI * 0.45 + 2.42;
-        let thickness = phi.cos().abs() * 2.0 + 1.0;
-        let line = gen_line(&Rectangle(bottom_left, bottom_left + pt2(step, step)), angle, thickness);
-        ret.push(line);
-        count += 1;
     }
     ret
 }
 
+const PARAMS: &[Param] = &[
+    Param {
+        name: "year",
+        default: "2023",
+        values: "a year",
+        description: "the year whose days are drawn",
+        parse: params::check::<i32>,
+    },
+    Param {
+        name: "latitude",
+        default: "51.5",
+        values: "-90 to 90",
+        description: "degrees north of the equator to measure the dark at",
+        parse: |value| params::check_range(value, -90.0_f64..=90.0),
+    },
+    Param {
+        name: "calendar",
+        default: "grid",
+        values: "grid, weeks, months, radial",
+        description: "how the days are laid out",
+        parse: params::check::<Layout>,
+    },
+    Param {
+        name: "labels",
+        default: "false",
+        values: "true, false",
+        description: "whether to name the months",
+        parse: params::check::<bool>,
+    },
+];
+
+pub struct HoursOfDark;
+
+impl Sketch for HoursOfDark {
+    type Model = Model;
+
+    const NAME: &'static str = "hours-of-dark";
+    const DESCRIPTION: &'static str =
+        "a line for each day of the year, turned by its hours of dark";
+
+    fn params() -> Vec<Param> {
+        PARAMS.to_vec()
+    }
+
+    fn generate(size: Vec2) -> Self::Model {
+        model(size)
+    }
+
+    fn draw(model: &Self::Model, canvas: &mut dyn Canvas) {
+        draw_model(model, canvas);
+    }
+}
+
+// one line for each day of `year`, darker for more hours of dark at `latitude` degrees north
+fn model(size: Vec2) -> Model {
+    let latitude = params::get("latitude", 51.5);
+    let year = params::get("year", 2023);
+    let labels = params::get("labels", false);
+    let calendar = match params::get("calendar", Layout::Grid) {
+        Layout::Grid => grid(year, size),
+        Layout::Weeks => weeks(year, labels),
+        Layout::Months => months(year, labels),
+        Layout::Radial => radial(year, labels),
+    };
+    let step = (size * 0.9 / calendar.extent).min_element();
+    let top_left = pt2(-calendar.extent.x, calendar.extent.y) * step / 2.0;
+    let to_point = |cell: Vec2| top_left + vec2(cell.x, -cell.y) * step;
+    let lines = calendar
+        .days
+        .iter()
+        .enumerate()
+        .map(|(day, &cell)| {
+            let dark = 1.0 - sun::daylight(latitude, year, day) as f32 / 24.0;
+            let angle = (1.0 - dark) * PI * 0.9 + 2.42;
+            let thickness = dark * 4.0;
+            let bottom_left = to_point(cell) - pt2(step, step) * 0.5;
+            gen_line(
+                &Rectangle(bottom_left, bottom_left + pt2(step, step)),
+                angle,
+                thickness,
+            )
+        })
+        .collect();
+    let labels = calendar
+        .labels
+        .iter()
+        .map(|&(name, cell)| (name, to_point(cell), calendar.label_size * step))
+        .collect();
+    Model { lines, labels }
+}
+
 fn gen_line(rect: &Rectangle, angle: f32, thickness: f32) -> Polygon {
     let mid = rect.bottom_left() + rect.span() / 2.0;
     let v = (0.05 * rect.height() * pt2(0.0, 1.0)) * thickness;
     let w = rect.width() * pt2(1.0, 0.0);
-    let mut ret = vec![
-        mid + v + w,
-        mid + v - w,
-        mid - v - w,
-        mid - v + w,
-    ];
+    let mut ret = vec![mid + v + w, mid + v - w, mid - v - w, mid - v + w];
     for p in &mut ret {
         common::rotate_about_point(p, &mid, angle);
     }
     clip(ret, rect)
 }
 
-fn view(app: &App, model: &Vec<Polygon>, frame: Frame) {
-    let draw = app.draw();
-    draw.background().color(WHITE);
-    for poly in model {
-        draw.polygon()
-            .points(poly.clone())
-            .color(BLACK);
+fn draw_model(model: &Model, canvas: &mut dyn Canvas) {
+    let palette = palette::current();
+    canvas.background(palette.background);
+    for poly in &model.lines {
+        canvas.polygon(poly, Some(palette.foreground), None);
+    }
+    for &(name, centre, size) in &model.labels {
+        text::draw(canvas, name, centre, size, palette.foreground);
     }
-    draw.to_frame(app, &frame).unwrap();
 }
 
 #[cfg(test)]
@@ -199,8 +393,8 @@ mod tests {
     }
 
     fn assert(poly: Polygon, mask: &Rectangle, expected: &Polygon) {
-        let clipped = clip(poly.clone(), &mask);
-        if !polygons_equivalent(&clipped, &expected) {
+        let clipped = clip(poly.clone(), mask);
+        if !polygons_equivalent(&clipped, expected) {
             eprintln!("original = {:?}", poly);
             eprintln!("clipped against {:?} = {:?}", mask, clipped);
             panic!();
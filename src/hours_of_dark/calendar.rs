@@ -0,0 +1,65 @@
+pub const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+pub fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+pub fn days_in_year(year: i32) -> usize {
+    if is_leap_year(year) {
+        366
+    } else {
+        365
+    }
+}
+
+pub fn month_lengths(year: i32) -> [usize; 12] {
+    let february = if is_leap_year(year) { 29 } else { 28 };
+    [31, february, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]
+}
+
+// the first day of each month, counting from zero on the first of january, and its length
+pub fn months(year: i32) -> Vec<(usize, usize)> {
+    month_lengths(year)
+        .iter()
+        .scan(0, |first, &length| {
+            let month = (*first, length);
+            *first += length;
+            Some(month)
+        })
+        .collect()
+}
+
+// the day of the week of the first of january, counting from zero on monday
+pub fn first_weekday(year: i32) -> usize {
+    // the first of january 1970 was a thursday
+    let days: i64 = if year >= 1970 {
+        (1970..year).map(|y| days_in_year(y) as i64).sum()
+    } else {
+        -(year..1970).map(|y| days_in_year(y) as i64).sum::<i64>()
+    };
+    (days + 3).rem_euclid(7) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leap_years_have_an_extra_day() {
+        assert_eq!(days_in_year(2023), 365);
+        assert_eq!(days_in_year(2024), 366);
+        assert_eq!(days_in_year(1900), 365);
+        assert_eq!(days_in_year(2000), 366);
+        assert_eq!(months(2024)[2], (60, 31));
+    }
+
+    #[test]
+    fn years_start_on_the_right_weekday() {
+        assert_eq!(first_weekday(1970), 3);
+        assert_eq!(first_weekday(2023), 6);
+        assert_eq!(first_weekday(2024), 0);
+        assert_eq!(first_weekday(1900), 0);
+    }
+}
@@ -0,0 +1,46 @@
+use super::calendar;
+
+// the sun's centre sits this far below the horizon at sunrise and sunset,
+// allowing for refraction and the radius of its disc
+const SUNRISE_ZENITH: f64 = 90.833;
+
+// the sun's declination in radians at noon on `day`, counting from zero on the first of January,
+// from the noaa's fourier series in the fractional year
+fn declination(year: i32, day: usize) -> f64 {
+    let gamma = std::f64::consts::TAU / calendar::days_in_year(year) as f64 * day as f64;
+    0.006918 - 0.399912 * gamma.cos() + 0.070257 * gamma.sin() - 0.006758 * (2.0 * gamma).cos()
+        + 0.000907 * (2.0 * gamma).sin()
+        - 0.002697 * (3.0 * gamma).cos()
+        + 0.00148 * (3.0 * gamma).sin()
+}
+
+// hours from sunrise to sunset at `latitude` degrees north, which are
+// zero through the polar night and twenty four through the midnight sun
+pub fn daylight(latitude: f64, year: i32, day: usize) -> f64 {
+    let latitude = latitude.clamp(-90.0, 90.0).to_radians();
+    let declination = declination(year, day);
+    let cos_hour_angle = SUNRISE_ZENITH.to_radians().cos() / (latitude.cos() * declination.cos())
+        - latitude.tan() * declination.tan();
+    // the hour angle turns through fifteen degrees an hour, both before and after noon
+    2.0 * cos_hour_angle.clamp(-1.0, 1.0).acos().to_degrees() / 15.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daylight_follows_the_seasons() {
+        // the equator has about twelve hours all year round
+        assert!((daylight(0.0, 2023, 0) - 12.1).abs() < 0.1);
+        assert!((daylight(0.0, 2023, 172) - 12.1).abs() < 0.1);
+        // london at the solstices
+        assert!((daylight(51.5, 2023, 171) - 16.6).abs() < 0.2);
+        assert!((daylight(51.5, 2023, 354) - 7.8).abs() < 0.2);
+        // the seasons are reversed in the south
+        assert!(daylight(-33.9, 2023, 0) > daylight(-33.9, 2023, 171));
+        // svalbard's midnight sun and polar night
+        assert_eq!(daylight(78.2, 2023, 171), 24.0);
+        assert_eq!(daylight(78.2, 2023, 354), 0.0);
+    }
+}
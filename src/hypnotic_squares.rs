@@ -1,7 +1,15 @@
+use crate::canvas::{Canvas, Outline};
 use crate::common;
-use itertools::Itertools;
+use crate::fills::{self, Fills};
+use crate::layout::{self, Cell};
+use crate::palette::{self, Palette};
+use crate::params;
+use crate::seed;
+use crate::sketch::{Param, Sketch};
+use crate::tween::Ease;
 use nannou::prelude::*;
 use rand::Rng;
+use std::iter;
 
 const FINAL_SQUARE_SIZE_PROPORTION: f32 = 0.1;
 
@@ -12,65 +20,328 @@ struct Square {
     dx: f32,
     dy: f32,
     steps: usize,
+    // how far through a step this square's nesting is when the animation starts
+    phase: f32,
 }
-type Model = Vec<Square>;
-
-pub fn run() {
-    nannou::app(generate_model)
-        .event(|a, m, e| common::refresh_model_on_space(a, m, e, generate_model))
-        .simple_window(view)
-        .size(800, 800)
-        .run();
-}
-
-fn generate_model(app: &App) -> Model {
-    let size = app.main_window().inner_size_points().0 * 0.9;
-    let n = 10_usize;
-    let start = -size / 2.0;
-    let step = size / n as f32;
-    let mut rng = rand::thread_rng();
-    let to_square = |(i, j): (usize, usize)| Square {
-        x: start + step * i as f32,
-        y: start + step * j as f32,
-        size: step,
-        dx: 0.4 * rng.gen_range(-1..=1) as f32,
-        dy: 0.4 * rng.gen_range(-1..=1) as f32,
-        steps: 3 + rng.gen_range(0..=2),
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Shape {
+    Square,
+    Circle,
+    Hexagon,
+    Triangle,
+}
+
+// how the nested squares step towards the edge of their cell
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Direction {
+    // a step to the side, up, down or along a diagonal, as in the tutorial
+    Grid,
+    // a step of the same length at any angle
+    Any,
+}
+
+// how the space between nested squares is coloured
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Bands {
+    Outline,
+    // shaded from the background at the edge of the cell to the foreground at the centre,
+    // or through the `fill`s when they are given
+    Filled,
+    Alternating,
+}
+
+pub struct Model {
+    squares: Vec<Square>,
+    seconds: f32,
+    // steps per second that the nested squares zoom inwards
+    speed: f32,
+    shape: Shape,
+    bands: Bands,
+    fills: Option<Fills>,
+    // eases the sizes of the nested squares from the cell's to the final one
+    spacing: Ease,
+    weight: f32,
+}
+
+impl std::str::FromStr for Shape {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "square" => Ok(Shape::Square),
+            "circle" => Ok(Shape::Circle),
+            "hexagon" => Ok(Shape::Hexagon),
+            "triangle" => Ok(Shape::Triangle),
+            _ => Err("expected one of square, circle, hexagon, triangle".to_owned()),
+        }
+    }
+}
+
+impl std::str::FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "grid" => Ok(Direction::Grid),
+            "any" => Ok(Direction::Any),
+            _ => Err("expected one of grid, any".to_owned()),
+        }
+    }
+}
+
+impl std::str::FromStr for Bands {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "outline" => Ok(Bands::Outline),
+            "filled" => Ok(Bands::Filled),
+            "alternating" => Ok(Bands::Alternating),
+            _ => Err("expected one of outline, filled, alternating".to_owned()),
+        }
+    }
+}
+
+const PARAMS: &[Param] = &[
+    Param {
+        name: "speed",
+        default: "0",
+        values: "steps per second",
+        description: "how fast the nested squares zoom inwards",
+        parse: |value| params::check_range(value, -f32::MAX..=f32::MAX),
+    },
+    Param {
+        name: "shape",
+        default: "square",
+        values: "square, circle, hexagon, triangle",
+        description: "the shape that is nested",
+        parse: params::check::<Shape>,
+    },
+    Param {
+        name: "direction",
+        default: "grid",
+        values: "grid, any",
+        description: "whether the shapes step along the grid or at any angle",
+        parse: params::check::<Direction>,
+    },
+    Param {
+        name: "spacing",
+        default: "linear",
+        values: "an easing curve, as for ease",
+        description: "how the shapes are spaced from the edge to the centre",
+        parse: params::check::<Ease>,
+    },
+    Param {
+        name: "bands",
+        default: "outline",
+        values: "outline, filled, alternating",
+        description: "how the bands between the shapes are coloured",
+        parse: params::check::<Bands>,
+    },
+    Param {
+        name: "min_steps",
+        default: "3",
+        values: "1 or more",
+        description: "the fewest shapes nested in a cell",
+        parse: |value| params::check_range(value, 1_usize..),
+    },
+    Param {
+        name: "max_steps",
+        default: "5",
+        values: "min_steps or more",
+        description: "the most shapes nested in a cell",
+        parse: |value| params::check_range(value, 1_usize..),
+    },
+];
+
+pub struct HypnoticSquares;
+
+impl Sketch for HypnoticSquares {
+    type Model = Model;
+
+    const NAME: &'static str = "hypnotic-squares";
+    const DESCRIPTION: &'static str = "squares nested towards a random corner of each cell";
+
+    fn params() -> Vec<Param> {
+        [PARAMS, layout::PARAMS, fills::PARAMS].concat()
+    }
+
+    fn generate(size: Vec2) -> Self::Model {
+        generate_model(size)
+    }
+
+    fn draw(model: &Self::Model, canvas: &mut dyn Canvas) {
+        draw_model(model, canvas);
+    }
+
+    fn animated() -> bool {
+        params::get("speed", 0.0_f32) != 0.0
+    }
+
+    fn update(model: &mut Self::Model, seconds: f32) {
+        model.seconds += seconds;
+    }
+
+    // the same squares at the new size, carrying on from the same point in the animation
+    fn resize(model: &mut Self::Model, size: Vec2) {
+        seed::set(seed::current());
+        *model = Model {
+            seconds: model.seconds,
+            ..generate_model(size)
+        };
+    }
+}
+
+fn generate_model(size: Vec2) -> Model {
+    Model {
+        squares: generate_squares(size),
+        seconds: 0.0,
+        speed: params::get("speed", 0.0),
+        shape: params::get("shape", Shape::Square),
+        bands: params::get("bands", Bands::Outline),
+        fills: Fills::from_params(),
+        spacing: params::get("spacing", "linear".parse().unwrap()),
+        weight: common::scaled(2.0, size),
+    }
+}
+
+fn generate_squares(size: Vec2) -> Vec<Square> {
+    let extent = size * 0.9;
+    let direction = params::get("direction", Direction::Grid);
+    let min_steps = params::get("min_steps", 3_usize).max(1);
+    let max_steps = params::get("max_steps", 5_usize).max(min_steps);
+    let mut rng = seed::rng();
+    let to_square = |cell: Cell| {
+        let (dx, dy) = match direction {
+            Direction::Grid => (
+                0.4 * rng.gen_range(-1..=1) as f32,
+                0.4 * rng.gen_range(-1..=1) as f32,
+            ),
+            Direction::Any => {
+                let angle = rng.gen_range(0.0..TAU);
+                (0.4 * angle.cos(), 0.4 * angle.sin())
+            }
+        };
+        Square {
+            x: cell.origin.x,
+            y: cell.origin.y,
+            size: cell.size,
+            dx,
+            dy,
+            steps: min_steps + rng.gen_range(0..=max_steps - min_steps),
+            phase: rng.gen_range(0.0..1.0),
+        }
     };
-    (0..n).cartesian_product(0..n).map(to_square).collect()
+    layout::cells(-extent / 2.0, extent, 10)
+        .into_iter()
+        .map(to_square)
+        .collect()
+}
+
+fn draw_shape(
+    shape: Shape,
+    centre: Point2,
+    size: f32,
+    fill: Option<Rgba>,
+    outline: Outline,
+    canvas: &mut dyn Canvas,
+) {
+    let outline = Some(outline);
+    // the number of corners and the angle of the first, with the square's along the cell's edges
+    let (corners, angle, radius) = match shape {
+        Shape::Circle => return canvas.ellipse(centre, size / 2.0, fill, outline),
+        Shape::Square => (4, PI / 4.0, size / 2.0 * 2.0.sqrt()),
+        Shape::Hexagon => (6, 0.0, size / 2.0),
+        Shape::Triangle => (3, PI / 2.0, size / 2.0),
+    };
+    let points: Vec<Point2> = (0..corners)
+        .map(|corner| {
+            let angle = angle + TAU * corner as f32 / corners as f32;
+            centre + vec2(angle.cos(), angle.sin()) * radius
+        })
+        .collect();
+    canvas.polygon(&points, fill, outline);
+}
+
+// how far in each square is drawn, with its index counted from the outside in,
+// following each one as it moves so that neighbours always alternate
+fn levels(steps: usize, progress: f32) -> impl Iterator<Item = (f32, i64)> {
+    let offset = progress.rem_euclid(1.0);
+    let cycle = progress.floor() as i64;
+    let between =
+        (0..steps).map(move |step| ((step as f32 + offset) / steps as f32, step as i64 - cycle));
+    // the outermost square lies beyond the first moving in, which covers it until it moves
+    let outer = (offset > 0.0).then_some((0.0, -cycle - 1));
+    outer
+        .into_iter()
+        .chain(between)
+        .chain(iter::once((1.0, steps as i64 - cycle)))
 }
 
 // note: contrary to the tutorial i decided to
 // implement draw_square non-recursively
 // the code worked out much cleaner this way
-fn draw_square(square: &Square, draw: &Draw) {
-    for step in 0..=square.steps {
-        let t = step as f32 / square.steps as f32;
+//
+// the outermost and innermost squares stay put while those between them
+// move inwards, so that a whole step of `progress` loops seamlessly
+fn draw_square(
+    square: &Square,
+    progress: f32,
+    model: &Model,
+    palette: &Palette,
+    canvas: &mut dyn Canvas,
+) {
+    for (t, index) in levels(square.steps, progress) {
+        let fill = match model.bands {
+            Bands::Outline => None,
+            Bands::Filled => Some(match &model.fills {
+                Some(fills) => fills.at(t),
+                None => palette.shade(t),
+            }),
+            Bands::Alternating if index.rem_euclid(2) == 0 => Some(palette.background),
+            Bands::Alternating => Some(palette.foreground),
+        };
+        let t = model.spacing.at(t);
         let size = square.size * (1.0 - t) + square.size * FINAL_SQUARE_SIZE_PROPORTION * t;
-        let (x, y) = {
-            let even_displacement = (square.size - size) / 2.0;
-            (
-                square.x + even_displacement + (square.size - size) * square.dx * 0.5,
-                square.y + even_displacement + (square.size - size) * square.dy * 0.5,
-            )
+        let centre = pt2(square.x, square.y)
+            + vec2(square.size, square.size) / 2.0
+            + vec2(square.dx, square.dy) * (square.size - size) * 0.5;
+        let outline = (model.weight, palette.foreground);
+        draw_shape(model.shape, centre, size, fill, outline, canvas);
+    }
+}
+
+fn draw_model(model: &Model, canvas: &mut dyn Canvas) {
+    let palette = palette::current();
+    canvas.background(palette.background);
+    for square in &model.squares {
+        let progress = if model.speed == 0.0 {
+            0.0
+        } else {
+            model.seconds * model.speed + square.phase
         };
-        draw.quad()
-            .rgba(0.0, 0.0, 0.0, 0.0)
-            .stroke_weight(2.0)
-            .points(
-                pt2(x, y),
-                pt2(x + size, y),
-                pt2(x + size, y + size),
-                pt2(x, y + size),
-            );
+        draw_square(square, progress, model, &palette, canvas);
     }
 }
 
-fn view(app: &App, model: &Model, frame: Frame) {
-    let draw = app.draw();
-    draw.background().color(WHITE);
-    for square in model {
-        draw_square(square, &draw);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighbouring_levels_alternate() {
+        for progress in [0.0, 0.99, 1.0, 1.01] {
+            let levels: Vec<_> = levels(4, progress).collect();
+            assert_eq!(levels.first().map(|level| level.0), Some(0.0));
+            assert_eq!(levels.last().map(|level| level.0), Some(1.0));
+            for pair in levels.windows(2) {
+                assert_ne!(
+                    pair[0].1.rem_euclid(2),
+                    pair[1].1.rem_euclid(2),
+                    "at {progress}: {levels:?}"
+                );
+            }
+        }
     }
-    draw.to_frame(app, &frame).unwrap();
 }
@@ -1,52 +1,169 @@
+use crate::canvas::Canvas;
 use crate::common;
+use crate::palette::{self, Palette};
+use crate::params;
+use crate::sketch::{Param, Sketch};
 use nannou::prelude::*;
-use rand::Rng;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+mod data;
+mod ridge;
 
 type Layer = Vec<Point2>;
 
-pub fn run() {
-    nannou::app(generate_layers)
-        .event(|app, model, event| {
-            common::refresh_model_on_space(app, model, event, generate_layers)
-        })
-        .simple_window(view)
-        .size(800, 800)
-        .run();
+enum Source {
+    Data(Vec<Vec<f32>>),
+    Synthetic(Box<ridge::Ridges>),
+}
+
+pub struct Model {
+    size: Vec2,
+    source: Source,
+    // the number of ridges on screen when still
+    n: usize,
+    // heights of the ridges from front to back, with one extra ridge
+    // at the back which fades in as the others scroll forwards
+    ridges: VecDeque<Vec<f32>>,
+    // the row index of the frontmost ridge
+    front: i64,
+    // how far the ridges have scrolled from their resting place, in ridges
+    offset: f32,
+    // ridges per second
+    speed: f32,
 }
 
-fn draw_layer(layer: &Layer, draw: &Draw) {
-    for top in layer.windows(2) {
-        draw.quad().color(WHITE).points(
-            pt2(top[0].x, -400.0),
-            top[0],
-            top[1],
-            pt2(top[1].x, -400.0),
-        );
+const PARAMS: &[Param] = &[
+    Param {
+        name: "data",
+        default: "none",
+        values: "a .csv or .wav file",
+        description: "data to draw the ridges from instead of noise",
+        parse: data::check,
+    },
+    Param {
+        name: "rows",
+        default: "30",
+        values: "1 or more",
+        description: "the number of ridges to split a wav file's envelope into",
+        parse: |value| params::check_range(value, 1_usize..),
+    },
+    Param {
+        name: "speed",
+        default: "0",
+        values: "0 or more ridges per second",
+        description: "how fast the ridges scroll forwards",
+        parse: |value| params::check_range(value, 0.0_f32..=f32::MAX),
+    },
+];
+
+pub struct JoyDivision;
+
+// the ridges scroll between frames, so the model is only generated once when recording
+impl Sketch for JoyDivision {
+    type Model = Model;
+
+    const NAME: &'static str = "joy-division";
+    const DESCRIPTION: &'static str = "ridges of noise stacked like the cover of unknown pleasures";
+
+    fn params() -> Vec<Param> {
+        [PARAMS, ridge::PARAMS].concat()
+    }
+
+    fn generate(size: Vec2) -> Self::Model {
+        generate(size)
+    }
+
+    fn draw(model: &Self::Model, canvas: &mut dyn Canvas) {
+        draw_model(model, canvas);
+    }
+
+    fn animated() -> bool {
+        true
+    }
+
+    fn update(model: &mut Self::Model, seconds: f32) {
+        advance(model, seconds);
+    }
+
+    // the ridges are laid out as they're drawn, so only the size changes
+    fn resize(model: &mut Self::Model, size: Vec2) {
+        model.size = size;
     }
-    draw.polyline().weight(2_f32).points(layer.clone());
 }
 
-fn generate_layers(app: &App) -> Vec<Layer> {
-    let n = 30;
-    let m = 30;
+impl Source {
+    const M: i32 = 30;
+
+    fn heights(&self, row: i64) -> Vec<f32> {
+        match self {
+            // the first row of data is the furthest back, and the data repeats as it scrolls
+            Source::Data(rows) => {
+                let row =
+                    &rows[(rows.len() as i64 - 1 - row).rem_euclid(rows.len() as i64) as usize];
+                (0..=Self::M)
+                    .map(|j| data::sample(row, j as f32 / Self::M as f32))
+                    .collect()
+            }
+            Source::Synthetic(ridges) => ridges.heights(row, Self::M),
+        }
+    }
+}
+
+fn generate(size: Vec2) -> Model {
+    let source = match params::try_get::<PathBuf>("data") {
+        Some(path) => Source::Data(
+            // the file was loaded when the parameter was checked, and holds at least one row
+            data::load(&path, params::get("rows", 30_usize)).expect("ridge data was checked"),
+        ),
+        None => Source::Synthetic(Box::new(ridge::Ridges::from_params())),
+    };
+    let n = match &source {
+        Source::Data(rows) => rows.len(),
+        Source::Synthetic(_) => 30,
+    };
+    Model {
+        size,
+        ridges: (0..=n as i64).map(|row| source.heights(row)).collect(),
+        source,
+        n,
+        front: 0,
+        offset: 0.0,
+        speed: params::get("speed", 0.0),
+    }
+}
+
+fn advance(model: &mut Model, seconds: f32) {
+    model.offset += model.speed * seconds;
+    while model.offset >= 1.0 {
+        model.ridges.pop_front();
+        model.front += 1;
+        let back = model.front + model.n as i64;
+        model.ridges.push_back(model.source.heights(back));
+        model.offset -= 1.0;
+    }
+}
+
+// the ridges from front to back, with the opacity of their outlines
+fn layers(model: &Model) -> Vec<(Layer, f32)> {
+    let m = Source::M;
+    let n = model.n;
     let line_resolution = 200;
     let step = 1.0_f32 / m as f32;
-    let height = app.main_window().inner_size_points().0;
+    let (width, height) = (model.size.x, model.size.y);
     let vstep = height * 0.85 / (n as f32);
-    let hstep = height / (m as f32);
-    let vstart = -height / 2_f32 + 50.0;
-    let hstart = -height / 2_f32;
-    let mut rng = rand::thread_rng();
+    let hstep = width / (m as f32);
+    let vstart = -height / 2_f32 + common::scaled(50.0, model.size);
+    let hstart = -width / 2_f32;
+    let peak = (m / 2 * m / 2) as f32 * 0.02_f32;
 
-    let mut make_spline = |index: i32| {
+    let make_spline = |index: usize, heights: &[f32]| {
         use splines::*;
-        let mut make_point = |j: i32| {
-            let variance = (m / 2 - (j - m / 2).abs()) as f32;
-            let deviation =
-                rng.gen_range(0.0_f32..1.0_f32) * vstep * variance * variance * 0.02_f32;
+        let base = vstart + vstep * (index as f32 - model.offset);
+        let make_point = |j: i32| {
             pt2(
                 hstart + (j as f32) * hstep,
-                vstart + vstep * (index as f32) + deviation,
+                base + heights[j as usize] * vstep * peak,
             )
         };
         Spline::from_vec(
@@ -57,23 +174,65 @@ fn generate_layers(app: &App) -> Vec<Layer> {
     };
 
     let mut ret = Vec::new();
-    for i in 0..n {
-        let spline = make_spline(i);
-        ret.push(
+    for (i, heights) in model.ridges.iter().enumerate() {
+        let alpha = match i {
+            0 => 1.0 - model.offset,
+            i if i == n => model.offset,
+            _ => 1.0,
+        };
+        if alpha <= 0.0 {
+            continue;
+        }
+        let spline = make_spline(i, heights);
+        ret.push((
             (0..line_resolution)
-                .map(|index| spline.sample(index as f32 / line_resolution as f32))
-                .filter_map(|x| x)
+                .filter_map(|index| spline.sample(index as f32 / line_resolution as f32))
                 .collect(),
-        );
+            alpha,
+        ));
     }
     ret
 }
 
-fn view(app: &App, model: &Vec<Layer>, frame: Frame) {
-    let draw = app.draw();
-    draw.background().color(WHITE);
-    for layer in model.iter().rev() {
-        draw_layer(layer, &draw);
+fn draw_layer(
+    layer: &Layer,
+    alpha: f32,
+    bottom: f32,
+    weight: f32,
+    palette: &Palette,
+    canvas: &mut dyn Canvas,
+) {
+    let (Some(first), Some(last)) = (layer.first(), layer.last()) else {
+        return;
+    };
+    let mut outline = layer.clone();
+    outline.push(pt2(last.x, bottom));
+    outline.push(pt2(first.x, bottom));
+    // hides the ridges behind
+    canvas.polygon(&outline, Some(palette.background), None);
+    let mut color = palette.foreground;
+    color.alpha = alpha;
+    canvas.polyline(layer, weight, color);
+}
+
+fn draw_model(model: &Model, canvas: &mut dyn Canvas) {
+    let palette = palette::current();
+    canvas.background(palette.background);
+    let weight = common::scaled(2.0, model.size);
+    for (layer, alpha) in layers(model).iter().rev() {
+        draw_layer(layer, *alpha, -model.size.y / 2.0, weight, &palette, canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speed_is_finite_and_never_negative() {
+        let speed = PARAMS.iter().find(|param| param.name == "speed").unwrap();
+        assert_eq!((speed.parse)("2.5"), Ok(()));
+        assert!((speed.parse)("inf").is_err());
+        assert!((speed.parse)("-1").is_err());
     }
-    draw.to_frame(app, &frame).unwrap();
 }
@@ -0,0 +1,169 @@
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+// the number of amplitude samples taken along each row of audio
+const ENVELOPE_RESOLUTION: usize = 64;
+
+// the decoded contents of the last file loaded, so it is read once however often the ridges are
+// generated, starting when the `data` parameter is checked
+static LOADED: RwLock<Option<(PathBuf, Contents)>> = RwLock::new(None);
+
+#[derive(Clone)]
+enum Contents {
+    Rows(Vec<Vec<f32>>),
+    Samples(Vec<f32>),
+}
+
+// loads rows of ridge heights, normalised to the range [0, 1],
+// from either a csv of samples or the amplitude of a wav file
+pub fn load(path: &Path, rows: usize) -> Result<Vec<Vec<f32>>, String> {
+    let mut data = match read(path)? {
+        Contents::Rows(data) => data,
+        Contents::Samples(samples) => envelope(&samples, rows, ENVELOPE_RESOLUTION),
+    };
+    if data.is_empty() {
+        return Err(format!("no ridge data found in {}", path.display()));
+    }
+    normalise(&mut data);
+    Ok(data)
+}
+
+// whether the file holds any ridge data, which is then ready for `load`
+pub fn check(value: &str) -> Result<(), String> {
+    load(Path::new(value), 1).map(|_| ())
+}
+
+fn read(path: &Path) -> Result<Contents, String> {
+    if let Some((loaded, contents)) = LOADED.read().unwrap().as_ref() {
+        if loaded == path {
+            return Ok(contents.clone());
+        }
+    }
+    let is_wav = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"));
+    let contents = if is_wav {
+        Contents::Samples(read_wav(path)?)
+    } else {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+        Contents::Rows(parse_csv(&text))
+    };
+    *LOADED.write().unwrap() = Some((path.to_owned(), contents.clone()));
+    Ok(contents)
+}
+
+// one row per line, ignoring any lines (such as headers) without numbers
+fn parse_csv(text: &str) -> Vec<Vec<f32>> {
+    text.lines()
+        .map(|line| {
+            line.split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+                .filter_map(|value| value.trim().parse().ok())
+                .collect::<Vec<f32>>()
+        })
+        .filter(|row| !row.is_empty())
+        .collect()
+}
+
+// the samples of all channels mixed down to mono
+fn read_wav(path: &Path) -> Result<Vec<f32>, String> {
+    let error = |e: hound::Error| format!("failed to read {}: {e}", path.display());
+    let mut reader = hound::WavReader::open(path).map_err(error)?;
+    let spec = reader.spec();
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>(),
+        hound::SampleFormat::Int => {
+            let max = (1_i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.map(|sample| sample as f32 / max))
+                .collect()
+        }
+    }
+    .map_err(error)?;
+    Ok(samples
+        .chunks(spec.channels as usize)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect())
+}
+
+// splits the audio into rows and takes the rms amplitude of `bins` windows along each,
+// with fewer rows when there are too few samples to fill them
+fn envelope(samples: &[f32], rows: usize, bins: usize) -> Vec<Vec<f32>> {
+    let rows = rows.min(samples.len().checked_div(bins).unwrap_or(0));
+    let window = samples.len().checked_div(rows * bins).unwrap_or(0);
+    if window == 0 {
+        return Vec::new();
+    }
+    samples
+        .chunks_exact(window)
+        .map(|chunk| (chunk.iter().map(|s| s * s).sum::<f32>() / window as f32).sqrt())
+        .collect::<Vec<_>>()
+        .chunks_exact(bins)
+        .take(rows)
+        .map(<[f32]>::to_vec)
+        .collect()
+}
+
+fn normalise(data: &mut [Vec<f32>]) {
+    let values = || data.iter().flatten().copied();
+    let min = values().fold(f32::INFINITY, f32::min);
+    let max = values().fold(f32::NEG_INFINITY, f32::max);
+    let range = if max > min { max - min } else { 1.0 };
+    for value in data.iter_mut().flatten() {
+        *value = (*value - min) / range;
+    }
+}
+
+// linearly interpolates a row at `x` in the range [0, 1]
+pub fn sample(row: &[f32], x: f32) -> f32 {
+    if row.len() < 2 {
+        return row.first().copied().unwrap_or(0.0);
+    }
+    let position = x.clamp(0.0, 1.0) * (row.len() - 1) as f32;
+    let index = (position.floor() as usize).min(row.len() - 2);
+    let t = position - index as f32;
+    row[index] * (1.0 - t) + row[index + 1] * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_rows_skip_headers() {
+        let rows = parse_csv("a,b,c\n1,2,3\n\n4, 5.5 ,6\n");
+        assert_eq!(rows, vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.5, 6.0]]);
+    }
+
+    #[test]
+    fn envelope_measures_each_window() {
+        let samples = [1.0, -1.0, 0.0, 0.0, 0.5, -0.5, 2.0, 2.0];
+        let rows = envelope(&samples, 2, 2);
+        assert_eq!(rows, vec![vec![1.0, 0.0], vec![0.5, 2.0]]);
+        assert!(envelope(&samples, 0, 2).is_empty());
+        assert_eq!(envelope(&samples, 8, 2).len(), 4);
+    }
+
+    #[test]
+    fn unreadable_files_are_errors() {
+        assert!(check("missing.csv").is_err());
+        assert!(check("missing.wav").is_err());
+    }
+
+    #[test]
+    fn normalised_data_spans_unit_range() {
+        let mut data = vec![vec![-2.0, 0.0], vec![2.0]];
+        normalise(&mut data);
+        assert_eq!(data, vec![vec![0.0, 0.5], vec![1.0]]);
+    }
+
+    #[test]
+    fn sampling_interpolates_between_values() {
+        let row = [0.0, 1.0, 0.0];
+        assert_eq!(sample(&row, 0.0), 0.0);
+        assert_eq!(sample(&row, 0.25), 0.5);
+        assert_eq!(sample(&row, 0.5), 1.0);
+        assert_eq!(sample(&row, 1.0), 0.0);
+    }
+}
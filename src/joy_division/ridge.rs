@@ -0,0 +1,240 @@
+use super::data;
+use crate::params;
+use crate::seed;
+use crate::sketch::Param;
+use nannou::noise::{NoiseFn, OpenSimplex, Seedable};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// the shape of a peak, as a function of the distance from its centre in half widths
+#[derive(Clone, Debug, PartialEq)]
+pub enum Envelope {
+    Triangle,
+    Gaussian,
+    Cosine,
+    // heights sampled evenly from one side of the peak to the other
+    Custom(Vec<f32>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Noise {
+    Uniform,
+    Simplex,
+}
+
+// generates ridge heights in the range [0, 1] from the joy division parameters
+pub struct Ridges {
+    envelope: Envelope,
+    noise: Noise,
+    peaks: usize,
+    width: f32,
+    along: f64,
+    across: f64,
+    // when set, the ridges repeat after this many rows
+    period: Option<i64>,
+    seed: u64,
+    simplex: OpenSimplex,
+}
+
+// the parameters of `Ridges`
+pub const PARAMS: &[Param] = &[
+    Param {
+        name: "envelope",
+        default: "triangle",
+        values: "triangle, gaussian, cosine, custom:<heights>",
+        description: "the shape of each peak",
+        parse: params::check::<Envelope>,
+    },
+    Param {
+        name: "peaks",
+        default: "1",
+        values: "1 or more",
+        description: "the number of peaks on each ridge",
+        parse: |value| params::check_range(value, 1_usize..),
+    },
+    Param {
+        name: "width",
+        default: "0.5",
+        values: "0 to 1",
+        description: "the half width of each peak as a fraction of the ridge",
+        parse: |value| params::check_range(value, 0.0_f32..=1.0),
+    },
+    Param {
+        name: "noise",
+        default: "uniform",
+        values: "uniform, simplex",
+        description: "the roughness of the ridges",
+        parse: params::check::<Noise>,
+    },
+    Param {
+        name: "along",
+        default: "4",
+        values: "0 or more cycles along a ridge",
+        description: "the frequency of simplex noise along the ridges",
+        parse: |value| params::check_range(value, 0.0..=f64::MAX),
+    },
+    Param {
+        name: "across",
+        default: "0.3",
+        values: "0 or more cycles per ridge",
+        description: "the frequency of simplex noise from one ridge to the next",
+        parse: |value| params::check_range(value, 0.0..=f64::MAX),
+    },
+    Param {
+        name: "period",
+        default: "none",
+        values: "1 or more rows",
+        description: "the number of rows after which the ridges repeat",
+        parse: |value| params::check_range(value, 1_i64..),
+    },
+];
+
+impl Envelope {
+    pub fn at(&self, u: f32) -> f32 {
+        if u.abs() >= 1.0 {
+            return 0.0;
+        }
+        match self {
+            Envelope::Triangle => (1.0 - u.abs()).powi(2),
+            Envelope::Gaussian => (-4.5 * u * u).exp(),
+            Envelope::Cosine => 0.5 + 0.5 * (u * std::f32::consts::PI).cos(),
+            Envelope::Custom(heights) => data::sample(heights, (u + 1.0) / 2.0),
+        }
+    }
+}
+
+impl std::str::FromStr for Envelope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "triangle" => Ok(Envelope::Triangle),
+            "gaussian" => Ok(Envelope::Gaussian),
+            "cosine" => Ok(Envelope::Cosine),
+            _ => {
+                let heights = s
+                    .strip_prefix("custom:")
+                    .ok_or("expected one of triangle, gaussian, cosine, custom:<heights>")?;
+                heights
+                    .split(',')
+                    .map(|h| h.trim().parse().map_err(|e| format!("{e}")))
+                    .collect::<Result<_, _>>()
+                    .map(Envelope::Custom)
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for Noise {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uniform" => Ok(Noise::Uniform),
+            "simplex" => Ok(Noise::Simplex),
+            _ => Err("expected one of uniform, simplex".to_owned()),
+        }
+    }
+}
+
+impl Ridges {
+    pub fn from_params() -> Self {
+        let seed = seed::rng().gen();
+        Ridges {
+            envelope: params::get("envelope", Envelope::Triangle),
+            noise: params::get("noise", Noise::Uniform),
+            peaks: params::get("peaks", 1),
+            width: params::get("width", 0.5),
+            along: params::get("along", 4.0),
+            across: params::get("across", 0.3),
+            period: params::try_get("period"),
+            seed,
+            simplex: OpenSimplex::new().set_seed(seed as u32),
+        }
+    }
+
+    // `m + 1` heights along the ridge of the given row, which are the same every time
+    pub fn heights(&self, row: i64, m: i32) -> Vec<f32> {
+        let row = match self.period {
+            Some(period) => row.rem_euclid(period),
+            None => row,
+        };
+        let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(row as u64));
+        // a single peak sits in the middle, several are scattered about it
+        let centres: Vec<f32> = match self.peaks {
+            1 => vec![0.5],
+            peaks => (0..peaks).map(|_| rng.gen_range(0.25..0.75)).collect(),
+        };
+        (0..=m)
+            .map(|j| {
+                let x = j as f32 / m as f32;
+                let envelope = centres
+                    .iter()
+                    .map(|centre| self.envelope.at((x - centre) / self.width))
+                    .fold(0.0, f32::max);
+                let noise = match self.noise {
+                    Noise::Uniform => rng.gen_range(0.0..1.0),
+                    // open simplex noise lies roughly within [-0.5, 0.5]
+                    Noise::Simplex => (self.simplex_at(x, row) as f32 + 0.5).clamp(0.0, 1.0),
+                };
+                envelope * noise
+            })
+            .collect()
+    }
+
+    fn simplex_at(&self, x: f32, row: i64) -> f64 {
+        let x = x as f64 * self.along;
+        match self.period {
+            // rows travel around a circle, so that the noise wraps with the period
+            Some(period) => {
+                let radius = period as f64 * self.across / std::f64::consts::TAU;
+                let angle = row as f64 / period as f64 * std::f64::consts::TAU;
+                self.simplex
+                    .get([x, radius * angle.cos(), radius * angle.sin()])
+            }
+            None => self.simplex.get([x, row as f64 * self.across]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn envelopes_peak_in_the_middle() {
+        for envelope in [
+            Envelope::Triangle,
+            Envelope::Gaussian,
+            Envelope::Cosine,
+            "custom:0,1,0".parse().unwrap(),
+        ] {
+            assert_eq!(envelope.at(0.0), 1.0);
+            assert_eq!(envelope.at(-1.0), 0.0);
+            assert!(envelope.at(0.5) < 1.0);
+        }
+    }
+
+    #[test]
+    fn triangle_matches_the_tutorial() {
+        let m = 30_i32;
+        for j in 0..=m {
+            let variance = (m / 2 - (j - m / 2).abs()) as f32 / (m / 2) as f32;
+            let u = (j as f32 / m as f32 - 0.5) / 0.5;
+            assert!((Envelope::Triangle.at(u) - variance * variance).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn params_reject_values_outside_their_ranges() {
+        let parse = |name: &str, value: &str| {
+            let param = PARAMS.iter().find(|param| param.name == name).unwrap();
+            (param.parse)(value)
+        };
+        assert!(parse("period", "0").is_err());
+        assert!(parse("peaks", "0").is_err());
+        assert!(parse("width", "1.5").is_err());
+        assert!(parse("along", "inf").is_err());
+        assert_eq!(parse("period", "12"), Ok(()));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::canvas::{Canvas, Inset};
+use crate::palette;
+use crate::params;
+use crate::seed;
+use crate::text;
+use clap::ValueEnum;
+use nannou::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+// details of a generation which can be written beneath it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Item {
+    Name,
+    Seed,
+    Params,
+}
+
+// what to write in the margin beneath a tutorial, given on the command line
+#[derive(Clone, Debug, Default)]
+pub struct Label {
+    pub tutorial: String,
+    pub caption: Option<String>,
+    pub items: Vec<Item>,
+    // the height of the margin in points, which the artwork shrinks to make room for
+    pub margin: f32,
+}
+
+static LABEL: RwLock<Option<Label>> = RwLock::new(None);
+
+pub fn set(label: Label) {
+    *LABEL.write().unwrap() = Some(label);
+}
+
+pub fn current() -> Label {
+    LABEL.read().unwrap().clone().unwrap_or_default()
+}
+
+// names the tutorial shown, for windows which move between them
+pub fn set_tutorial(name: &str) {
+    if let Some(label) = LABEL.write().unwrap().as_mut() {
+        label.tutorial = name.to_owned();
+    }
+}
+
+impl Label {
+    fn details(&self) -> String {
+        self.items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Name => Some(self.tutorial.clone()),
+                Item::Seed => Some(format!("seed {}", seed::current())),
+                Item::Params => {
+                    let params = params::all();
+                    (!params.is_empty()).then(|| {
+                        params
+                            .iter()
+                            .map(|(key, value)| format!("{key}={value}"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    })
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ·  ")
+    }
+
+    // the lines of text beneath the artwork and their heights as fractions of the margin
+    fn lines(&self) -> Vec<(String, f32)> {
+        let caption = self.caption.clone().map(|caption| (caption, 0.3));
+        let details = Some(self.details())
+            .filter(|details| !details.is_empty())
+            .map(|details| (details, 0.2));
+        caption.into_iter().chain(details).collect()
+    }
+}
+
+// the scale and offset the artwork is drawn with to leave `margin` beneath it
+fn inset(margin: f32, size: Vec2) -> (f32, Vec2) {
+    ((size.y - margin) / size.y, vec2(0.0, margin / 2.0))
+}
+
+// where a point on the canvas, such as the cursor, lies on the artwork `frame` draws
+pub fn unframe(point: Point2, size: Vec2) -> Point2 {
+    let label = LABEL.read().unwrap();
+    match label.as_ref().filter(|label| label.margin > 0.0) {
+        Some(label) => {
+            let (scale, offset) = inset(label.margin.min(size.y / 2.0), size);
+            (point - offset) / scale
+        }
+        None => point,
+    }
+}
+
+// draws a tutorial's artwork, `size` points across, shrinking it to leave room for its label
+pub fn frame(canvas: &mut dyn Canvas, size: Vec2, draw: impl FnOnce(&mut dyn Canvas)) {
+    let label = LABEL.read().unwrap();
+    let Some(label) = label.as_ref().filter(|label| label.margin > 0.0) else {
+        return draw(canvas);
+    };
+    let margin = label.margin.min(size.y / 2.0);
+    let (scale, offset) = inset(margin, size);
+    draw(&mut Inset::new(canvas, scale, offset));
+    // the lines are spread evenly down the margin
+    let lines = label.lines();
+    let bottom = -size.y / 2.0;
+    for (i, (line, height)) in lines.iter().enumerate() {
+        let y = bottom + margin * (lines.len() - i) as f32 / (lines.len() + 1) as f32;
+        let contours = text::outlines_within(line, pt2(0.0, y), margin * height, size.x * 0.9);
+        canvas.shape(&contours, palette::current().foreground);
+    }
+}
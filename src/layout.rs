@@ -0,0 +1,309 @@
+use crate::params;
+use crate::seed;
+use crate::sketch::Param;
+use crate::tween::{self, Tween};
+use itertools::Itertools;
+use nannou::prelude::*;
+use rand::Rng;
+
+// a square region of the canvas, with `scale` relative to a cell of the regular grid
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cell {
+    pub origin: Point2,
+    pub size: f32,
+    pub scale: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Layout {
+    Grid,
+    Quadtree,
+}
+
+// the probability of a quadtree cell splitting, relative to `split`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Density {
+    Uniform,
+    Radial,
+}
+
+// the parameters of tutorials laid out with `cells`
+pub const PARAMS: &[Param] = &[
+    Param {
+        name: "layout",
+        default: "grid",
+        values: "grid, quadtree",
+        description: "how the cells are laid out",
+        parse: params::check::<Layout>,
+    },
+    Param {
+        name: "min_depth",
+        default: "1",
+        values: "0 or more",
+        description: "the fewest times a quadtree splits each cell",
+        parse: params::check::<usize>,
+    },
+    Param {
+        name: "max_depth",
+        default: "as fine as the grid",
+        values: "0 or more, up to where cells are a pixel across",
+        description: "the most times a quadtree splits each cell",
+        parse: params::check::<usize>,
+    },
+    Param {
+        name: "split",
+        default: "0.5",
+        values: "0 to 1",
+        description: "the chance a quadtree cell splits between the depths",
+        parse: |value| params::check_range(value, 0.0_f32..=1.0),
+    },
+    Param {
+        name: "density",
+        default: "uniform",
+        values: "uniform, radial",
+        description: "where a quadtree splits, with radial splitting most at the centre",
+        parse: params::check::<Density>,
+    },
+];
+
+impl Cell {
+    pub fn centre(&self) -> Point2 {
+        self.origin + vec2(self.size, self.size) / 2.0
+    }
+
+    // the cell scaled about the centre of the canvas
+    pub fn scaled(&self, factor: f32) -> Cell {
+        Cell {
+            origin: self.origin * factor,
+            size: self.size * factor,
+            scale: self.scale,
+        }
+    }
+
+    // position within the rectangle, such that grid cell (i, j) is at about (i, j) / (n - 1)
+    pub fn fraction(&self, start: Point2, extent: Vec2) -> Vec2 {
+        let span = extent - Vec2::splat(self.size);
+        let position = self.origin - start;
+        let along = |position: f32, span: f32| {
+            if span <= 0.0 {
+                0.5
+            } else {
+                (position / span).clamp(0.0, 1.0)
+            }
+        };
+        vec2(along(position.x, span.x), along(position.y, span.y))
+    }
+
+    fn quarters(&self) -> [Cell; 4] {
+        let size = self.size / 2.0;
+        let scale = self.scale / 2.0;
+        let cell = |x: f32, y: f32| Cell {
+            origin: self.origin + vec2(x, y) * size,
+            size,
+            scale,
+        };
+        [
+            cell(0.0, 0.0),
+            cell(1.0, 0.0),
+            cell(1.0, 1.0),
+            cell(0.0, 1.0),
+        ]
+    }
+}
+
+impl Tween for Cell {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Cell {
+            origin: self.origin.lerp(other.origin, t),
+            size: tween::mix(self.size, other.size, t),
+            scale: tween::mix(self.scale, other.scale, t),
+        }
+    }
+
+    fn collapsed(&self) -> Self {
+        Cell {
+            origin: self.centre(),
+            size: 0.0,
+            scale: 0.0,
+        }
+    }
+}
+
+impl std::str::FromStr for Layout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "grid" => Ok(Layout::Grid),
+            "quadtree" => Ok(Layout::Quadtree),
+            _ => Err("expected one of grid, quadtree".to_owned()),
+        }
+    }
+}
+
+impl std::str::FromStr for Density {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uniform" => Ok(Density::Uniform),
+            "radial" => Ok(Density::Radial),
+            _ => Err("expected one of uniform, radial".to_owned()),
+        }
+    }
+}
+
+// the square cells covering a rectangle from its bottom left `start`,
+// which is `n` cells across its shorter side when laid out as a grid
+pub fn cells(start: Point2, extent: Vec2, n: usize) -> Vec<Cell> {
+    match params::get("layout", Layout::Grid) {
+        Layout::Grid => grid(start, extent, n),
+        Layout::Quadtree => {
+            // cells stop splitting before they shrink below a pixel, however deep is asked for
+            let max_depth = params::get("max_depth", (n as f32).log2().ceil() as usize)
+                .min(pixel_depth(extent));
+            // a cell never splits past the max depth, even when the min is deeper
+            let min_depth = params::get("min_depth", 1).min(max_depth);
+            let split = params::get("split", 0.5_f32);
+            let density = params::get("density", Density::Uniform);
+            let centre = start + extent / 2.0;
+            let radius = extent.min_element() / 2.0;
+            let mut rng = seed::rng();
+            quadtree(start, extent, n, |cell, depth| {
+                if depth < min_depth {
+                    return true;
+                }
+                if depth >= max_depth {
+                    return false;
+                }
+                let probability = match density {
+                    Density::Uniform => split,
+                    Density::Radial => {
+                        split * (1.0 - (cell.centre() - centre).length() / radius).max(0.0)
+                    }
+                };
+                rng.gen_range(0.0..1.0) < probability
+            })
+        }
+    }
+}
+
+// `n` cells across the shorter side and as many as fit along the longer, centred along it
+pub fn grid(start: Point2, extent: Vec2, n: usize) -> Vec<Cell> {
+    let step = extent.min_element() / n as f32;
+    let counts = (extent / step + Vec2::splat(1e-3)).floor();
+    let start = start + ((extent - counts * step) / 2.0).max(Vec2::ZERO);
+    (0..counts.x as usize)
+        .cartesian_product(0..counts.y as usize)
+        .map(|(i, j)| Cell {
+            origin: pt2(start.x + i as f32 * step, start.y + j as f32 * step),
+            size: step,
+            scale: 1.0,
+        })
+        .collect()
+}
+
+// how many times the shorter side of a rectangle halves before it is under a pixel
+fn pixel_depth(extent: Vec2) -> usize {
+    extent.min_element().max(1.0).log2().floor() as usize
+}
+
+// squares tiling the rectangle, each as large as fits in what is left of it,
+// until what is left is thinner than `min`
+fn squares(start: Point2, extent: Vec2, min: f32) -> Vec<(Point2, f32)> {
+    let (mut start, mut extent) = (start, extent);
+    let mut ret = Vec::new();
+    while extent.min_element() > min * 0.999 {
+        let side = extent.min_element();
+        // the direction along what is left
+        let along = if extent.x >= extent.y {
+            Vec2::X
+        } else {
+            Vec2::Y
+        };
+        let count = (extent.dot(along) / side + 1e-3).floor();
+        for k in 0..count as usize {
+            ret.push((start + along * side * k as f32, side));
+        }
+        start += along * side * count;
+        extent -= along * side * count;
+    }
+    ret
+}
+
+// recursively splits squares cut from the rectangle into quarters for as long as `split` returns
+// true, with those smaller than its shorter side starting as deep as their size
+pub fn quadtree<F: FnMut(&Cell, usize) -> bool>(
+    start: Point2,
+    extent: Vec2,
+    n: usize,
+    mut split: F,
+) -> Vec<Cell> {
+    let side = extent.min_element();
+    let mut pending: Vec<_> = squares(start, extent, side / n as f32)
+        .into_iter()
+        .map(|(origin, size)| {
+            let cell = Cell {
+                origin,
+                size,
+                scale: n as f32 * size / side,
+            };
+            (cell, (side / size).log2().floor() as usize)
+        })
+        .collect();
+    let mut ret = Vec::new();
+    while let Some((cell, depth)) = pending.pop() {
+        if split(&cell, depth) {
+            pending.extend(cell.quarters().map(|quarter| (quarter, depth + 1)));
+        } else {
+            ret.push(cell);
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_cell_fractions_match_their_indices() {
+        let cells = grid(pt2(-1.0, -1.0), vec2(2.0, 2.0), 5);
+        let fraction = |index: usize| cells[index].fraction(pt2(-1.0, -1.0), vec2(2.0, 2.0));
+        assert!(fraction(0).abs_diff_eq(vec2(0.0, 0.0), 1e-6));
+        assert!(fraction(7).abs_diff_eq(vec2(0.25, 0.5), 1e-6));
+        assert!(fraction(24).abs_diff_eq(vec2(1.0, 1.0), 1e-6));
+    }
+
+    #[test]
+    fn quadtree_covers_the_square() {
+        let cells = quadtree(pt2(-1.0, -1.0), vec2(2.0, 2.0), 4, |cell, depth| {
+            depth < 3 && cell.origin.x < 0.0
+        });
+        let area: f32 = cells.iter().map(|cell| cell.size * cell.size).sum();
+        assert_eq!(area, 4.0);
+        assert_eq!(
+            cells.iter().map(|cell| cell.size).reduce(f32::min),
+            Some(0.25)
+        );
+        assert!(cells.iter().all(|cell| cell.scale == cell.size * 2.0));
+    }
+
+    #[test]
+    fn quadtrees_stop_at_a_pixel() {
+        assert_eq!(pixel_depth(vec2(1024.0, 768.0)), 9);
+        assert_eq!(pixel_depth(vec2(0.5, 0.5)), 0);
+    }
+
+    #[test]
+    fn rectangles_are_covered_by_square_cells() {
+        let cells = grid(pt2(0.0, 0.0), vec2(3.75, 1.0), 2);
+        assert_eq!(cells.len(), 14);
+        assert!(cells[0].origin.abs_diff_eq(pt2(0.125, 0.0), 1e-6));
+        // squares 1, 0.6 and 0.4 across, leaving a sliver thinner than a cell
+        let cells = quadtree(pt2(0.0, 0.0), vec2(1.6, 1.0), 4, |_, _| false);
+        let sizes: Vec<_> = cells.iter().map(|cell| cell.size).collect();
+        assert_eq!(sizes.len(), 3);
+        assert!((sizes.iter().sum::<f32>() - 1.0 - 0.6 - 0.4).abs() < 1e-5);
+    }
+}
@@ -1,48 +1,562 @@
-use clap::{Parser, ValueEnum};
+use clap::builder::{PossibleValuesParser, RangedU64ValueParser};
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use nannou::prelude::Vec2;
+use std::path::{Path, PathBuf};
 
+mod canvas;
 mod circle_packing;
 mod common;
 mod cubic_disarray;
+mod fills;
+mod gallery;
 mod hours_of_dark;
 mod hypnotic_squares;
 mod joy_division;
+mod label;
+mod layout;
+mod metadata;
+mod page;
+mod palette;
+mod params;
 mod piet_mondrian;
+mod record;
+mod seed;
+mod sketch;
+mod svg;
+mod text;
 mod tiled_lines;
 mod triangular_mesh;
+mod truchet;
+mod tween;
 mod un_deux_trois;
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
-enum Tutorial {
-    TiledLines,
-    JoyDivision,
-    CubicDisarray,
-    TriangularMesh,
-    UnDeuxTrois,
-    CirclePacking,
-    HypnoticSquares,
-    PietMondrian,
-    HoursOfDark,
-}
-
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
-    #[arg(value_enum)]
-    tutorial: Tutorial,
+    #[command(subcommand)]
+    command: Option<Command>,
+    // a tutorial can also be given without a subcommand, to run it or render it with `--frames`
+    #[command(flatten)]
+    sketch: SketchArgs,
+    #[command(flatten)]
+    export: ExportArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List the tutorials
+    List,
+    /// Show a tutorial's parameters, with their defaults and the values they take
+    Describe {
+        #[arg(value_parser = PossibleValuesParser::new(sketch::possible_values()))]
+        tutorial: String,
+    },
+    /// Open a tutorial in a window
+    Run(SketchArgs),
+    /// Render a tutorial to files without opening a window, one frame unless given `--frames`
+    Render {
+        #[command(flatten)]
+        sketch: SketchArgs,
+        #[command(flatten)]
+        export: ExportArgs,
+    },
+    /// Render a grid of thumbnails of a tutorial with consecutive seeds to one SVG or PNG,
+    /// each labelled with its seed unless given other `--label`s
+    ContactSheet {
+        #[command(flatten)]
+        sketch: SketchArgs,
+        /// The number of thumbnails across and down
+        #[arg(long, default_value_t = 4, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        grid: usize,
+        /// The side of each thumbnail
+        #[arg(long, value_name = "POINTS", default_value_t = 300.0, value_parser = parse_positive)]
+        thumbnail: f32,
+        /// The file to render to, an SVG or a PNG
+        #[arg(long, value_name = "FILE", default_value = "contact-sheet.png", value_parser = record::parse_sheet_path)]
+        out: PathBuf,
+    },
+    /// Render a tutorial with a parameter swept through a range or list of values, all from
+    /// the same seed, to a file for each value or to one labelled grid with `--sheet`
+    Sweep {
+        #[command(flatten)]
+        sketch: SketchArgs,
+        /// The parameter and its values, as `key=from..to` or `key=a,b,c`
+        #[arg(value_name = "KEY=VALUES", value_parser = params::parse_sweep)]
+        sweep: (String, params::Sweep),
+        /// The number of values a range is split into, including both ends
+        #[arg(long, default_value_t = 5, value_parser = RangedU64ValueParser::<usize>::new().range(2..))]
+        steps: usize,
+        /// The directory to render a file for each value into
+        #[arg(long, default_value = "sweep")]
+        out: PathBuf,
+        /// The format of the files for each value
+        #[arg(long, value_enum, default_value_t = record::Format::Png)]
+        format: record::Format,
+        /// Render one grid of thumbnails, labelled with their parameters, to this SVG or PNG instead
+        #[arg(long, value_name = "FILE", value_parser = record::parse_sheet_path)]
+        sheet: Option<PathBuf>,
+        /// The number of thumbnails across the grid, as close to square as possible by default
+        #[arg(long, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        columns: Option<usize>,
+        /// The side of each thumbnail in the grid
+        #[arg(long, value_name = "POINTS", default_value_t = 300.0, value_parser = parse_positive)]
+        thumbnail: f32,
+    },
+    /// Cycle through the tutorials in one window, with a fresh seed for each generation.
+    /// Left and Right move between tutorials, Space shows the next generation and P pauses
+    Gallery {
+        /// The tutorials to show, all of them by default
+        #[arg(long = "tutorial", value_delimiter = ',', value_parser = PossibleValuesParser::new(sketch::possible_values()))]
+        tutorials: Vec<String>,
+        /// How long each generation is shown for, unless paused with P
+        #[arg(long, default_value_t = 20.0, value_parser = parse_positive)]
+        seconds: f32,
+        /// How many generations of each tutorial to show before moving on
+        #[arg(long, default_value_t = 1)]
+        seeds: usize,
+        #[command(flatten)]
+        options: Options,
+    },
+    /// Render a piece, contact sheet or swept grid again from the metadata in a PNG or SVG
+    /// rendered before, or its JSON file
+    Reproduce {
+        file: PathBuf,
+        /// The file to render to, an SVG if it ends in .svg and a PNG otherwise,
+        /// by default beside the original with `-reproduced` added to its name
+        #[arg(long, value_parser = record::parse_sheet_path)]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Args)]
+struct SketchArgs {
+    #[arg(required = true, value_parser = PossibleValuesParser::new(sketch::possible_values()))]
+    tutorial: Option<String>,
+    #[command(flatten)]
+    options: Options,
+}
+
+// the options for drawing any tutorial
+#[derive(Args)]
+struct Options {
+    /// Set a tutorial parameter, e.g. `--param tiles=smith`
+    #[arg(short, long = "param", value_name = "KEY=VALUE", value_parser = params::parse_key_value)]
+    params: Vec<(String, String)>,
+    /// Start from this seed, so a generation can be repeated
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Write a caption beneath the artwork
+    #[arg(long)]
+    caption: Option<String>,
+    /// Write these details beneath the artwork
+    #[arg(long = "label", value_enum, value_delimiter = ',')]
+    labels: Vec<label::Item>,
+    /// The height of the margin for the caption and details, 60 points when there are any
+    #[arg(long, value_name = "POINTS")]
+    margin: Option<f32>,
+    /// The colours to draw in: classic (the default), night, paper, blueprint or sunset,
+    /// or a .gpl, .ase or hex list file of the background, foreground and accents
+    #[arg(long, value_name = "NAME|FILE", value_parser = palette::parse)]
+    palette: Option<palette::Palette>,
+    /// Draw on a sheet of paper instead of the 800 point square
+    #[arg(long, value_enum, conflicts_with = "size")]
+    paper: Option<page::Paper>,
+    /// Turn the paper on its side
+    #[arg(long, value_enum, default_value_t = page::Orientation::Portrait, requires = "paper")]
+    orientation: page::Orientation,
+    /// The width and height of the canvas in points, or with `mm` or `in`, e.g. `1200x800` or `200x150mm`
+    #[arg(long, value_name = "WIDTHxHEIGHT", value_parser = page::parse_size)]
+    size: Option<Vec2>,
+    /// Leave this much of the page blank around the artwork
+    #[arg(long, value_name = "MM", default_value_t = 0.0)]
+    page_margin: f32,
+    /// The units of rendered SVGs' width and height, millimetres with `--paper` and points otherwise
+    #[arg(long, value_enum)]
+    units: Option<page::Units>,
+    /// The resolution of rendered PNGs in pixels per inch, with a point 1/96 of an inch
+    #[arg(long, default_value_t = page::INCH, value_parser = parse_positive)]
+    dpi: f32,
+    /// Draw every stroke of a render with a plotter's pen this many millimetres wide
+    #[arg(long, value_name = "MM", value_parser = parse_positive)]
+    pen: Option<f32>,
+}
+
+#[derive(Args)]
+struct ExportArgs {
+    /// Render this many frames to numbered files instead of opening a window
+    #[arg(long, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    frames: Option<usize>,
+    /// The directory to render frames into
+    #[arg(long, default_value = "frames")]
+    out: PathBuf,
+    /// The format of the rendered frames, with text as outlines in SVGs
+    #[arg(long, value_enum, default_value_t = record::Format::Png)]
+    format: record::Format,
+    /// The frame rate of rendered animations
+    #[arg(long, default_value_t = 30.0, value_parser = parse_positive)]
+    fps: f32,
+    /// Also assemble the rendered frames into a GIF or animated PNG
+    #[arg(long, value_name = "FILE", value_parser = record::parse_animation_path)]
+    animation: Option<PathBuf>,
 }
 
 fn main() {
     let cli = Cli::parse();
-    use Tutorial::*;
-    match cli.tutorial {
-        TiledLines => tiled_lines::run(),
-        JoyDivision => joy_division::run(),
-        CubicDisarray => cubic_disarray::run(),
-        TriangularMesh => triangular_mesh::run(),
-        UnDeuxTrois => un_deux_trois::run(),
-        CirclePacking => circle_packing::run(),
-        HypnoticSquares => hypnotic_squares::run(),
-        PietMondrian => piet_mondrian::run(),
-        HoursOfDark => hours_of_dark::run(),
+    match cli.command {
+        Some(Command::List) => list(),
+        Some(Command::Describe { tutorial }) => describe(sketch::find(&tutorial).unwrap()),
+        Some(Command::Run(args)) => (setup(args).run)(),
+        Some(Command::Render { sketch, export }) => render(setup(sketch), export),
+        Some(Command::ContactSheet {
+            mut sketch,
+            grid,
+            thumbnail,
+            out,
+        }) => {
+            let options = &mut sketch.options;
+            if options.labels.is_empty() {
+                options.labels.push(label::Item::Seed);
+            }
+            // the label is written at the size the thumbnails are generated at, then scaled down
+            options.margin = options.margin.or(Some(120.0));
+            let sketch = setup(sketch);
+            let first = seed::current();
+            exit_on_error((sketch.contact_sheet)(&record::Sheet {
+                cells: (0..grid * grid)
+                    .map(|i| record::Cell {
+                        seed: first + i as u64,
+                        params: Vec::new(),
+                    })
+                    .collect(),
+                columns: grid,
+                thumbnail,
+                out,
+            }));
+        }
+        Some(Command::Sweep {
+            mut sketch,
+            sweep: (key, sweep),
+            steps,
+            out,
+            format,
+            sheet,
+            columns,
+            thumbnail,
+        }) => {
+            let values = sweep.values(steps);
+            let options = &mut sketch.options;
+            // every value is checked along with the tutorial's other parameters before any is drawn
+            options
+                .params
+                .extend(values.iter().map(|value| (key.clone(), value.clone())));
+            if sheet.is_some() {
+                if options.labels.is_empty() {
+                    options.labels.push(label::Item::Params);
+                }
+                options.margin = options.margin.or(Some(120.0));
+            }
+            let sketch = setup(sketch);
+            // every value is drawn from the same seed, so only the parameter changes
+            let seed = seed::current();
+            exit_on_error(match sheet {
+                Some(path) => (sketch.contact_sheet)(&record::Sheet {
+                    columns: columns.unwrap_or((values.len() as f32).sqrt().ceil() as usize),
+                    cells: values
+                        .iter()
+                        .map(|value| record::Cell {
+                            seed,
+                            params: vec![(key.clone(), value.clone())],
+                        })
+                        .collect(),
+                    thumbnail,
+                    out: path,
+                }),
+                None => (sketch.sweep)(&record::Sweep {
+                    key,
+                    values,
+                    seed,
+                    out,
+                    format,
+                }),
+            });
+        }
+        Some(Command::Gallery {
+            tutorials,
+            seconds,
+            seeds,
+            options,
+        }) => {
+            let sketches: Vec<_> = if tutorials.is_empty() {
+                sketch::SKETCHES.iter().collect()
+            } else {
+                tutorials
+                    .iter()
+                    .map(|name| sketch::find(name).unwrap())
+                    .collect()
+            };
+            apply(options, &sketches);
+            gallery::run(gallery::Options {
+                sketches,
+                seconds,
+                seeds: seeds.max(1),
+            });
+        }
+        Some(Command::Reproduce { file, out }) => reproduce(&file, out),
+        None => {
+            let sketch = setup(cli.sketch);
+            match cli.export.frames {
+                Some(_) => render(sketch, cli.export),
+                None => (sketch.run)(),
+            }
+        }
+    }
+}
+
+fn list() {
+    let width = sketch::SKETCHES
+        .iter()
+        .map(|entry| entry.name.len())
+        .max()
+        .unwrap_or(0);
+    for entry in sketch::SKETCHES {
+        println!("{:width$}  {}", entry.name, entry.description);
+    }
+}
+
+fn describe(sketch: &sketch::Entry) {
+    println!("{}: {}", sketch.name, sketch.description);
+    let params = (sketch.params)();
+    let width = params
+        .iter()
+        .map(|param| param.name.len())
+        .max()
+        .unwrap_or(0);
+    println!();
+    for param in params {
+        println!("  {:width$}  {}", param.name, param.description);
+        println!(
+            "  {:width$}  {} (default {})",
+            "", param.values, param.default
+        );
+    }
+}
+
+// applies the options shared by every mode, returning the tutorial they're for
+fn setup(args: SketchArgs) -> &'static sketch::Entry {
+    // clap requires the tutorial whenever these arguments are parsed
+    let sketch = sketch::find(args.tutorial.as_deref().unwrap()).unwrap();
+    apply(args.options, &[sketch]);
+    sketch
+}
+
+// sets the parameters, seed, palette and label for the sketches, refusing any
+// parameter none of them take or whose value doesn't parse
+fn apply(options: Options, sketches: &[&sketch::Entry]) {
+    for (key, value) in &options.params {
+        let declared: Vec<_> = sketches
+            .iter()
+            .flat_map(|sketch| (sketch.params)())
+            .filter(|param| param.name == key)
+            .collect();
+        if declared.is_empty() {
+            let message = match sketches {
+                [sketch] => {
+                    let params: Vec<_> = (sketch.params)()
+                        .iter()
+                        .map(sketch::Param::summary)
+                        .collect();
+                    format!(
+                        "{} has no parameter `{key}`, but takes:\n{}",
+                        sketch.name,
+                        params.join("\n")
+                    )
+                }
+                _ => format!("no tutorial takes a parameter `{key}`, see `describe <TUTORIAL>`"),
+            };
+            Cli::command()
+                .error(ErrorKind::InvalidValue, message)
+                .exit();
+        }
+        for param in &declared {
+            if let Err(e) = (param.parse)(value) {
+                let message = format!(
+                    "invalid value `{value}` for parameter `{key}` ({}): {e}",
+                    param.values
+                );
+                Cli::command()
+                    .error(ErrorKind::InvalidValue, message)
+                    .exit();
+            }
+        }
+        params::set(key, value);
+    }
+    if let Some(seed) = options.seed {
+        seed::set(seed);
+    }
+    if let Some(palette) = options.palette {
+        palette::set(palette);
+    }
+    let size = match (options.paper, options.size) {
+        (Some(paper), _) => paper.size(options.orientation),
+        (None, Some(size)) => size,
+        (None, None) => page::Page::default().size,
+    };
+    let units = match options.paper {
+        Some(_) => page::Units::Mm,
+        None => page::Units::Px,
+    };
+    page::set(page::Page {
+        size,
+        margin: options.page_margin * page::MM,
+        units: options.units.unwrap_or(units),
+        dpi: options.dpi,
+        pen: options.pen.map(|pen| pen * page::MM),
+    });
+    let labelled = options.caption.is_some() || !options.labels.is_empty();
+    label::set(label::Label {
+        tutorial: sketches[0].name.to_owned(),
+        caption: options.caption,
+        items: options.labels,
+        margin: options.margin.unwrap_or(if labelled { 60.0 } else { 0.0 }),
+    });
+}
+
+// renders a piece again with the tutorial, seed, parameters, palette, page and label it was rendered with
+fn reproduce(file: &Path, out: Option<PathBuf>) {
+    fn fail(message: String) -> ! {
+        Cli::command()
+            .error(ErrorKind::InvalidValue, message)
+            .exit()
+    }
+    let metadata = metadata::read(file).unwrap_or_else(|e| fail(e));
+    let Some(sketch) = sketch::find(&metadata.tutorial) else {
+        fail(format!(
+            "{} was rendered by `{}`, which isn't a tutorial",
+            file.display(),
+            metadata.tutorial
+        ))
+    };
+    let version = env!("CARGO_PKG_VERSION");
+    if metadata.version != version {
+        eprintln!(
+            "warning: {} was rendered by version {}, and version {version} may draw it differently",
+            file.display(),
+            metadata.version
+        );
+    }
+    metadata
+        .apply(&(sketch.params)())
+        .unwrap_or_else(|e| fail(e));
+    let out = out.unwrap_or_else(|| {
+        let extension = match file.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("svg") => "svg",
+            _ => "png",
+        };
+        let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+        file.with_file_name(format!("{stem}-reproduced.{extension}"))
+    });
+    let rendered = match metadata.sheet {
+        Some(sheet) => (sketch.contact_sheet)(&record::Sheet {
+            cells: sheet
+                .cells
+                .into_iter()
+                .map(|cell| record::Cell {
+                    seed: cell.seed,
+                    params: cell.params.into_iter().collect(),
+                })
+                .collect(),
+            columns: sheet.columns,
+            thumbnail: sheet.thumbnail,
+            out,
+        }),
+        None => {
+            let time = record::Time {
+                frame: metadata.frame,
+                fps: metadata.fps,
+            };
+            (sketch.still)(&out, time)
+        }
+    };
+    rendered.unwrap_or_else(|e| fail(e));
+}
+
+fn render(sketch: &sketch::Entry, export: ExportArgs) {
+    let options = record::Options {
+        frames: export.frames.unwrap_or(1),
+        fps: export.fps,
+        out: export.out,
+        format: export.format,
+        animation: export.animation,
     };
+    exit_on_error((sketch.record)(&options));
+}
+
+// exits with a rendering's error, such as an image too large to render
+fn exit_on_error(result: Result<(), String>) {
+    if let Err(message) = result {
+        Cli::command()
+            .error(ErrorKind::InvalidValue, message)
+            .exit();
+    }
+}
+
+// a number greater than zero, for rates and sizes
+fn parse_positive(arg: &str) -> Result<f32, String> {
+    match arg.parse::<f32>() {
+        Ok(value) if value.is_finite() && value > 0.0 => Ok(value),
+        Ok(_) => Err(format!("expected a number greater than 0, found `{arg}`")),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tutorials_can_be_given_with_or_without_a_subcommand() {
+        Cli::command().debug_assert();
+        let cli = Cli::parse_from(["art", "tiled-lines", "--frames", "2"]);
+        assert!(cli.command.is_none());
+        assert_eq!(cli.sketch.tutorial.as_deref(), Some("tiled-lines"));
+        assert_eq!(cli.export.frames, Some(2));
+        let cli = Cli::parse_from(["art", "render", "joy-division", "-p", "speed=4"]);
+        let Some(Command::Render { sketch, export }) = cli.command else {
+            panic!("expected the render subcommand");
+        };
+        assert_eq!(
+            sketch.options.params,
+            [("speed".to_owned(), "4".to_owned())]
+        );
+        assert_eq!(export.frames, None);
+        assert!(Cli::try_parse_from(["art", "describe"]).is_err());
+        assert!(Cli::try_parse_from(["art", "list", "tiled-lines"]).is_err());
+        let cli = Cli::parse_from(["art", "gallery", "--tutorial", "joy-division,piet-mondrian"]);
+        let Some(Command::Gallery { tutorials, .. }) = cli.command else {
+            panic!("expected the gallery subcommand");
+        };
+        assert_eq!(tutorials, ["joy-division", "piet-mondrian"]);
+        assert!(Cli::try_parse_from(["art", "gallery", "--tutorial", "truchet"]).is_err());
+        assert!(Cli::try_parse_from(["art", "gallery", "--seconds", "NaN"]).is_err());
+        assert!(
+            Cli::try_parse_from(["art", "contact-sheet", "tiled-lines", "--out", "a.gif"]).is_err()
+        );
+        for (arg, value) in [
+            ("--fps", "0"),
+            ("--dpi", "0"),
+            ("--pen", "nan"),
+            ("--frames", "0"),
+        ] {
+            let args = ["art", "render", "tiled-lines", arg, value];
+            assert!(Cli::try_parse_from(args).is_err());
+        }
+        let args = ["art", "sweep", "tiled-lines", "n=4..40", "--steps", "1"];
+        assert!(Cli::try_parse_from(args).is_err());
+        for (arg, value) in [("--grid", "0"), ("--thumbnail", "0")] {
+            let args = ["art", "contact-sheet", "tiled-lines", arg, value];
+            assert!(Cli::try_parse_from(args).is_err());
+        }
+    }
 }
@@ -0,0 +1,305 @@
+use crate::label::{self, Item};
+use crate::page::{self, Page, Units};
+use crate::palette;
+use crate::params;
+use crate::record::Time;
+use crate::seed;
+use crate::sketch::Param;
+use nannou::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+// the keyword of the png text chunk holding the metadata, and the id of the svg element
+const KEYWORD: &str = "generative-artistry";
+
+// everything a rendered piece was made with, so that it can be made again
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Metadata {
+    pub tutorial: String,
+    // the version of this crate
+    pub version: String,
+    pub seed: u64,
+    // the parameters given, which are all a piece is reproduced with
+    pub params: BTreeMap<String, String>,
+    // the defaults of the rest, for reference
+    pub defaults: BTreeMap<String, String>,
+    // how far an animation had run, which is the start for still tutorials
+    pub frame: usize,
+    pub fps: f32,
+    // the background, foreground and accents
+    pub palette: Vec<String>,
+    pub page: PageMetadata,
+    pub label: LabelMetadata,
+    // the thumbnails of a contact sheet or swept grid, which are drawn over the seed and params
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sheet: Option<SheetMetadata>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PageMetadata {
+    // in points, as are the margin and pen
+    pub width: f32,
+    pub height: f32,
+    pub margin: f32,
+    pub units: Units,
+    pub dpi: f32,
+    pub pen: Option<f32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LabelMetadata {
+    pub caption: Option<String>,
+    pub items: Vec<Item>,
+    pub margin: f32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SheetMetadata {
+    pub columns: usize,
+    pub thumbnail: f32,
+    pub cells: Vec<CellMetadata>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CellMetadata {
+    pub seed: u64,
+    pub params: BTreeMap<String, String>,
+}
+
+impl Metadata {
+    // the piece being rendered now, with the tutorial's `declared` parameters
+    pub fn current(tutorial: &str, declared: &[Param], time: Time) -> Self {
+        let params: BTreeMap<_, _> = params::all().into_iter().collect();
+        let defaults = declared
+            .iter()
+            .filter(|param| !params.contains_key(param.name))
+            .map(|param| (param.name.to_owned(), param.default.to_owned()))
+            .collect();
+        let palette = palette::current();
+        let page = page::current();
+        let label = label::current();
+        Metadata {
+            tutorial: tutorial.to_owned(),
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            seed: seed::current(),
+            params,
+            defaults,
+            frame: time.frame,
+            fps: time.fps,
+            palette: [palette.background, palette.foreground]
+                .iter()
+                .chain(&palette.accents)
+                .map(|color| palette::hex(*color))
+                .collect(),
+            page: PageMetadata {
+                width: page.size.x,
+                height: page.size.y,
+                margin: page.margin,
+                units: page.units,
+                dpi: page.dpi,
+                pen: page.pen,
+            },
+            label: LabelMetadata {
+                caption: label.caption,
+                items: label.items,
+                margin: label.margin,
+            },
+            sheet: None,
+        }
+    }
+
+    // sets the seed, parameters, palette, page and label back to the piece's, refusing any
+    // parameter the tutorial doesn't take or whose value doesn't parse
+    pub fn apply(&self, declared: &[Param]) -> Result<(), String> {
+        let cells = self.sheet.iter().flat_map(|sheet| &sheet.cells);
+        for (key, value) in cells.flat_map(|cell| &cell.params).chain(&self.params) {
+            let param = declared
+                .iter()
+                .find(|param| param.name == key)
+                .ok_or_else(|| format!("{} has no parameter `{key}`", self.tutorial))?;
+            (param.parse)(value)
+                .map_err(|e| format!("invalid value `{value}` for parameter `{key}`: {e}"))?;
+        }
+        if !(self.fps.is_finite() && self.fps > 0.0) {
+            return Err(format!(
+                "expected a frame rate greater than 0, found {}",
+                self.fps
+            ));
+        }
+        seed::set(self.seed);
+        for (key, value) in &self.params {
+            params::set(key, value);
+        }
+        let colors = self
+            .palette
+            .iter()
+            .map(|color| palette::parse_hex(color))
+            .collect::<Result<_, _>>()?;
+        palette::set(palette::from_colors(colors)?);
+        page::set(Page {
+            size: vec2(self.page.width, self.page.height),
+            margin: self.page.margin,
+            units: self.page.units,
+            dpi: self.page.dpi,
+            pen: self.page.pen,
+        });
+        label::set(label::Label {
+            tutorial: self.tutorial.clone(),
+            caption: self.label.caption.clone(),
+            items: self.label.items.clone(),
+            margin: self.label.margin,
+        });
+        Ok(())
+    }
+
+    // json with anything outside ascii escaped, so that it fits in a png's latin-1 text
+    pub fn to_json(&self) -> String {
+        let json = serde_json::to_string_pretty(self).unwrap();
+        let mut ascii = String::with_capacity(json.len());
+        for c in json.chars() {
+            if c.is_ascii() {
+                ascii.push(c);
+            } else {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    ascii.push_str(&format!("\\u{unit:04x}"));
+                }
+            }
+        }
+        ascii
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("invalid metadata: {e}"))
+    }
+
+    // the text chunks of a png: the standard title and software, and the metadata itself
+    pub fn png_text(&self) -> Vec<(String, String)> {
+        vec![
+            ("Title".to_owned(), self.tutorial.clone()),
+            ("Software".to_owned(), format!("{KEYWORD} {}", self.version)),
+            (KEYWORD.to_owned(), self.to_json()),
+        ]
+    }
+
+    // an svg element, with the json escaped as xml text
+    pub fn svg_element(&self) -> String {
+        let json = self
+            .to_json()
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        format!("<metadata id=\"{KEYWORD}\">\n{json}\n</metadata>")
+    }
+
+    // writes the json beside a rendered file, with the same name
+    pub fn write_sidecar(&self, path: &Path) -> Result<(), String> {
+        let sidecar = sidecar(path);
+        std::fs::write(&sidecar, self.to_json() + "\n")
+            .map_err(|e| format!("failed to write {}: {e}", sidecar.display()))
+    }
+}
+
+pub fn sidecar(path: &Path) -> PathBuf {
+    path.with_extension("json")
+}
+
+// reads the metadata from a png's text, an svg's metadata element or a json sidecar
+pub fn read(path: &Path) -> Result<Metadata, String> {
+    let read_error = |e: std::io::Error| format!("failed to read {}: {e}", path.display());
+    let missing = || format!("{} has no metadata to reproduce it from", path.display());
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+    let json = match extension.as_deref() {
+        Some("png") => {
+            let file = std::fs::File::open(path).map_err(read_error)?;
+            let reader = png::Decoder::new(file)
+                .read_info()
+                .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+            let chunk = reader
+                .info()
+                .uncompressed_latin1_text
+                .iter()
+                .find(|chunk| chunk.keyword == KEYWORD)
+                .ok_or_else(missing)?;
+            chunk.text.clone()
+        }
+        Some("svg") => {
+            let document = std::fs::read_to_string(path).map_err(read_error)?;
+            svg_json(&document).ok_or_else(missing)?
+        }
+        _ => std::fs::read_to_string(path).map_err(read_error)?,
+    };
+    Metadata::from_json(&json).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+// the json in an svg's metadata element
+fn svg_json(document: &str) -> Option<String> {
+    let start = format!("<metadata id=\"{KEYWORD}\">");
+    let (_, rest) = document.split_once(&start)?;
+    let (json, _) = rest.split_once("</metadata>")?;
+    Some(
+        json.trim()
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> Metadata {
+        Metadata {
+            tutorial: "piet-mondrian".to_owned(),
+            version: "0.1.0".to_owned(),
+            seed: 42,
+            params: [("fill".to_owned(), "gradient:#1b3a6b,#f2c14e".to_owned())].into(),
+            defaults: [("hue".to_owned(), "random".to_owned())].into(),
+            frame: 0,
+            fps: 30.0,
+            palette: vec!["#ffffff".to_owned(), "#000000".to_owned()],
+            page: PageMetadata {
+                width: 800.0,
+                height: 600.0,
+                margin: 0.0,
+                units: Units::Mm,
+                dpi: 300.0,
+                pen: None,
+            },
+            label: LabelMetadata {
+                caption: Some("Composition <No. 1> — “blue” & red".to_owned()),
+                items: vec![Item::Name, Item::Seed],
+                margin: 60.0,
+            },
+            sheet: Some(SheetMetadata {
+                columns: 2,
+                thumbnail: 300.0,
+                cells: vec![CellMetadata {
+                    seed: 43,
+                    params: [("hue".to_owned(), "120".to_owned())].into(),
+                }],
+            }),
+        }
+    }
+
+    #[test]
+    fn writes_metadata_as_ascii_json_and_reads_it_back() {
+        let metadata = metadata();
+        let json = metadata.to_json();
+        assert!(json.is_ascii());
+        assert_eq!(Metadata::from_json(&json), Ok(metadata.clone()));
+        let document = format!("<svg>\n{}\n</svg>\n", metadata.svg_element());
+        assert_eq!(svg_json(&document), Some(json));
+        assert_eq!(svg_json("<svg></svg>"), None);
+        // pieces rendered on their own have no sheet
+        let single = Metadata {
+            sheet: None,
+            ..metadata
+        };
+        assert!(!single.to_json().contains("\"sheet\""));
+        assert_eq!(Metadata::from_json(&single.to_json()), Ok(single));
+    }
+}
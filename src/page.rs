@@ -0,0 +1,167 @@
+use clap::ValueEnum;
+use nannou::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+// points per inch and per millimetre, with points as css pixels
+pub const INCH: f32 = 96.0;
+pub const MM: f32 = INCH / 25.4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Paper {
+    A4,
+    A3,
+    Letter,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+// the units an svg's width and height are given in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    Px,
+    Mm,
+    In,
+}
+
+// the canvas the tutorials are drawn on, with a blank margin around the artwork, both in points
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Page {
+    pub size: Vec2,
+    pub margin: f32,
+    pub units: Units,
+    // the pixels per inch of rendered images
+    pub dpi: f32,
+    // the width of a plotter's pen in points, which every stroke is drawn with
+    pub pen: Option<f32>,
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Page {
+            size: vec2(800.0, 800.0),
+            margin: 0.0,
+            units: Units::Px,
+            dpi: INCH,
+            pen: None,
+        }
+    }
+}
+
+impl Page {
+    pub fn pixels_per_point(&self) -> f32 {
+        self.dpi / INCH
+    }
+}
+
+impl Units {
+    // a length in points written in these units, such as `210mm`,
+    // to a thousandth of a unit so that paper sizes come out whole
+    pub fn format(self, points: f32) -> String {
+        let round = |length: f32| (length * 1000.0).round() / 1000.0;
+        match self {
+            Units::Px => format!("{points}"),
+            Units::Mm => format!("{}mm", round(points / MM)),
+            Units::In => format!("{}in", round(points / INCH)),
+        }
+    }
+}
+
+impl Paper {
+    // the width and height in millimetres, upright
+    fn millimetres(self) -> Vec2 {
+        match self {
+            Paper::A4 => vec2(210.0, 297.0),
+            Paper::A3 => vec2(297.0, 420.0),
+            Paper::Letter => vec2(215.9, 279.4),
+        }
+    }
+
+    pub fn size(self, orientation: Orientation) -> Vec2 {
+        let size = self.millimetres() * MM;
+        match orientation {
+            Orientation::Portrait => size,
+            Orientation::Landscape => vec2(size.y, size.x),
+        }
+    }
+}
+
+// accepts a size in points such as `1200x800`, or in millimetres or inches such as `200x150mm`
+pub fn parse_size(arg: &str) -> Result<Vec2, String> {
+    let error =
+        || format!("expected a width and height such as `1200x800` or `200x150mm`, found `{arg}`");
+    let (arg, scale) = if let Some(arg) = arg.strip_suffix("mm") {
+        (arg, MM)
+    } else if let Some(arg) = arg.strip_suffix("in") {
+        (arg, INCH)
+    } else {
+        (arg, 1.0)
+    };
+    let (width, height) = arg.split_once('x').ok_or_else(error)?;
+    let dimension = |s: &str| {
+        s.trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|d| d.is_finite() && *d > 0.0)
+    };
+    match (dimension(width), dimension(height)) {
+        (Some(width), Some(height)) => Ok(vec2(width, height) * scale),
+        _ => Err(error()),
+    }
+}
+
+// the page given on the command line
+static PAGE: RwLock<Option<Page>> = RwLock::new(None);
+
+pub fn set(page: Page) {
+    *PAGE.write().unwrap() = Some(page);
+}
+
+pub fn current() -> Page {
+    PAGE.read().unwrap().unwrap_or_default()
+}
+
+// the size of the artwork on a canvas `size` across, inside the page's margin,
+// which is scaled along with the canvas when it isn't the page's own size
+pub fn artwork(size: Vec2) -> Vec2 {
+    let page = current();
+    let margin = page.margin * size.min_element() / page.size.min_element();
+    (size - Vec2::splat(2.0 * margin)).max(Vec2::ONE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_paper_and_parses_sizes() {
+        let a4 = Paper::A4.size(Orientation::Landscape);
+        assert!((a4.x - 1122.5).abs() < 0.1 && (a4.y - 793.7).abs() < 0.1);
+        assert_eq!(parse_size("1200x800"), Ok(vec2(1200.0, 800.0)));
+        assert!(parse_size("1200").is_err());
+        assert!(parse_size("0x800").is_err());
+        assert!(parse_size("infx800").is_err());
+        assert!(parse_size("1200xNaN").is_err());
+        assert_eq!(parse_size("8.5x11in"), Ok(vec2(816.0, 1056.0)));
+        assert_eq!(
+            parse_size("210x297mm"),
+            Ok(Paper::A4.size(Orientation::Portrait))
+        );
+    }
+
+    #[test]
+    fn formats_lengths_in_units() {
+        assert_eq!(Units::Px.format(96.0), "96");
+        assert_eq!(Units::In.format(96.0), "1in");
+        assert_eq!(Units::Mm.format(MM * 25.0), "25mm");
+        assert_eq!(
+            Units::Mm.format(Paper::A4.size(Orientation::Portrait).y),
+            "297mm"
+        );
+    }
+}
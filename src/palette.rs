@@ -0,0 +1,309 @@
+use crate::tween;
+use nannou::prelude::*;
+use std::path::Path;
+use std::sync::RwLock;
+
+// the colours a tutorial is drawn in: the paper, the ink, and any
+// accents for tutorials that colour some of their shapes
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    pub background: Rgba,
+    pub foreground: Rgba,
+    pub accents: Vec<Rgba>,
+}
+
+// background, foreground and accents as 0xrrggbb
+const NAMED: &[(&str, u32, u32, &[u32])] = &[
+    // black on white, with piet mondrian's yellow, red and blue
+    (
+        "classic",
+        0xffffff,
+        0x000000,
+        &[0xf7d844, 0xd40920, 0x1356a2],
+    ),
+    ("night", 0x000000, 0xffffff, &[0xf7d844, 0xd40920, 0x1356a2]),
+    ("paper", 0xf4efe1, 0x2b2a33, &[0xc8553d, 0x588b8b, 0xf2d0a4]),
+    (
+        "blueprint",
+        0x1d3f6e,
+        0xe8f1f8,
+        &[0x9ec5e8, 0x5b8fc7, 0xf5f5f5],
+    ),
+    (
+        "sunset",
+        0xfff1e0,
+        0x3d1c4b,
+        &[0xff6b35, 0xf7c548, 0xc0392b],
+    ),
+];
+
+// the palette given on the command line
+static PALETTE: RwLock<Option<Palette>> = RwLock::new(None);
+
+pub fn set(palette: Palette) {
+    *PALETTE.write().unwrap() = Some(palette);
+}
+
+pub fn current() -> Palette {
+    PALETTE.read().unwrap().clone().unwrap_or_default()
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        named("classic").unwrap()
+    }
+}
+
+impl Palette {
+    // a tone between the background at zero and the foreground at one
+    pub fn shade(&self, t: f32) -> Rgba {
+        let (from, to) = (self.background, self.foreground);
+        Rgba::new(
+            tween::mix(from.red, to.red, t),
+            tween::mix(from.green, to.green, t),
+            tween::mix(from.blue, to.blue, t),
+            tween::mix(from.alpha, to.alpha, t),
+        )
+    }
+
+    // the accents in turn, falling back on the foreground when there are none
+    pub fn accent(&self, index: usize) -> Rgba {
+        if self.accents.is_empty() {
+            return self.foreground;
+        }
+        self.accents[index % self.accents.len()]
+    }
+}
+
+fn rgb(value: u32) -> Rgba {
+    let component = |shift: u32| ((value >> shift) & 0xff) as f32 / 255.0;
+    Rgba::new(component(16), component(8), component(0), 1.0)
+}
+
+fn named(name: &str) -> Option<Palette> {
+    NAMED
+        .iter()
+        .find(|(n, ..)| *n == name)
+        .map(|(_, background, foreground, accents)| Palette {
+            background: rgb(*background),
+            foreground: rgb(*foreground),
+            accents: accents.iter().map(|accent| rgb(*accent)).collect(),
+        })
+}
+
+// accepts a named palette, or a file of colours: a gimp palette (.gpl), an adobe
+// swatch exchange file (.ase), or anything else as a list of hex colours
+pub fn parse(arg: &str) -> Result<Palette, String> {
+    if let Some(palette) = named(arg) {
+        return Ok(palette);
+    }
+    let path = Path::new(arg);
+    if !path.is_file() {
+        let names: Vec<_> = NAMED.iter().map(|(name, ..)| *name).collect();
+        return Err(format!(
+            "expected one of {} or a palette file, found `{arg}`",
+            names.join(", ")
+        ));
+    }
+    let read_error = |e: std::io::Error| format!("failed to read {arg}: {e}");
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+    let colors = match extension.as_deref() {
+        Some("gpl") => gpl(&std::fs::read_to_string(path).map_err(read_error)?),
+        Some("ase") => ase(&std::fs::read(path).map_err(read_error)?),
+        _ => hex_list(&std::fs::read_to_string(path).map_err(read_error)?),
+    }?;
+    from_colors(colors)
+}
+
+// the first colour is the background, the second the foreground and the rest accents
+pub fn from_colors(colors: Vec<Rgba>) -> Result<Palette, String> {
+    match colors.as_slice() {
+        [background, foreground, accents @ ..] => Ok(Palette {
+            background: *background,
+            foreground: *foreground,
+            accents: accents.to_vec(),
+        }),
+        _ => Err("a palette needs at least a background and a foreground colour".to_owned()),
+    }
+}
+
+// a colour such as `#1356a2`, without its alpha
+pub fn hex(color: Rgba) -> String {
+    let byte = |component: f32| (component.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        byte(color.red),
+        byte(color.green),
+        byte(color.blue)
+    )
+}
+
+pub fn parse_hex(s: &str) -> Result<Rgba, String> {
+    let digits = s.trim_start_matches('#');
+    match u32::from_str_radix(digits, 16) {
+        Ok(value) if digits.len() == 6 => Ok(rgb(value)),
+        _ => Err(format!("expected a colour such as `#1356a2`, found `{s}`")),
+    }
+}
+
+// colours such as `#1356a2` or `1356a2`, separated by whitespace or commas
+fn hex_list(text: &str) -> Result<Vec<Rgba>, String> {
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .map(parse_hex)
+        .collect()
+}
+
+// a `GIMP Palette` header, then a colour per line as red, green and blue from 0 to 255
+// followed by an optional name, around comments and `Name:` and `Columns:` fields
+fn gpl(text: &str) -> Result<Vec<Rgba>, String> {
+    let mut lines = text.lines();
+    if lines.next().map(str::trim) != Some("GIMP Palette") {
+        return Err("expected a `GIMP Palette` header".to_owned());
+    }
+    lines
+        .map(str::trim)
+        .filter(|line| {
+            !(line.is_empty()
+                || line.starts_with('#')
+                || line.starts_with("Name:")
+                || line.starts_with("Columns:"))
+        })
+        .map(|line| {
+            let components: Vec<u8> = line
+                .split_whitespace()
+                .take(3)
+                .map(|component| component.parse::<u8>())
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("invalid colour `{line}`: {e}"))?;
+            match components.as_slice() {
+                [r, g, b] => Ok(Rgba::new(
+                    *r as f32 / 255.0,
+                    *g as f32 / 255.0,
+                    *b as f32 / 255.0,
+                    1.0,
+                )),
+                _ => Err(format!("expected red, green and blue, found `{line}`")),
+            }
+        })
+        .collect()
+}
+
+// reads big endian values from the front of a slice
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.0.len() < len {
+            return Err("the swatch file ends early".to_owned());
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+// the colour entries of an adobe swatch exchange file, in order and ignoring their groups
+fn ase(bytes: &[u8]) -> Result<Vec<Rgba>, String> {
+    const COLOR_ENTRY: u16 = 0x0001;
+    let mut reader = Reader(bytes);
+    if reader.take(4)? != b"ASEF" {
+        return Err("expected an adobe swatch exchange file".to_owned());
+    }
+    // the version
+    reader.take(4)?;
+    let blocks = reader.u32()?;
+    let mut colors = Vec::new();
+    for _ in 0..blocks {
+        let kind = reader.u16()?;
+        let len = reader.u32()? as usize;
+        let mut block = Reader(reader.take(len)?);
+        if kind != COLOR_ENTRY {
+            continue;
+        }
+        // the name, in utf-16 code units
+        let name_len = block.u16()? as usize;
+        block.take(name_len * 2)?;
+        let color = match block.take(4)? {
+            b"RGB " => Rgba::new(block.f32()?, block.f32()?, block.f32()?, 1.0),
+            b"Gray" => {
+                let value = block.f32()?;
+                Rgba::new(value, value, value, 1.0)
+            }
+            b"CMYK" => {
+                let [c, m, y, k] = [block.f32()?, block.f32()?, block.f32()?, block.f32()?];
+                Rgba::new(
+                    (1.0 - c) * (1.0 - k),
+                    (1.0 - m) * (1.0 - k),
+                    (1.0 - y) * (1.0 - k),
+                    1.0,
+                )
+            }
+            model => {
+                return Err(format!(
+                    "unsupported colour model `{}`",
+                    String::from_utf8_lossy(model).trim()
+                ))
+            }
+        };
+        colors.push(color);
+    }
+    Ok(colors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_hex_lists_and_gimp_palettes() {
+        assert_eq!(
+            hex_list("#ffffff, 000000\nd40920\n"),
+            Ok(vec![rgb(0xffffff), rgb(0x000000), rgb(0xd40920)])
+        );
+        assert!(hex_list("#fff").is_err());
+        let gimp = "GIMP Palette\nName: Test\nColumns: 2\n# comment\n255 255 255\tWhite\n  0   0   0\tBlack\n";
+        assert_eq!(gpl(gimp), Ok(vec![rgb(0xffffff), rgb(0x000000)]));
+        assert!(gpl("255 255 255").is_err());
+    }
+
+    #[test]
+    fn reads_swatch_exchange_files() {
+        let mut bytes = b"ASEF\0\x01\0\0\0\0\0\x03".to_vec();
+        // a group, which is skipped, around an rgb and a gray colour entry
+        bytes.extend([0xc0, 0x01, 0, 0, 0, 0]);
+        for (model, values) in [(b"RGB ", vec![1.0f32, 0.0, 0.0]), (b"Gray", vec![0.5])] {
+            let mut block = vec![0, 1, 0, 0];
+            block.extend(model);
+            for value in values {
+                block.extend(value.to_be_bytes());
+            }
+            block.extend([0, 2]);
+            bytes.extend([0, 1]);
+            bytes.extend((block.len() as u32).to_be_bytes());
+            bytes.extend(block);
+        }
+        assert_eq!(
+            ase(&bytes),
+            Ok(vec![
+                Rgba::new(1.0, 0.0, 0.0, 1.0),
+                Rgba::new(0.5, 0.5, 0.5, 1.0)
+            ])
+        );
+        assert!(ase(b"GIMP").is_err());
+    }
+}
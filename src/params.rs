@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::ops::RangeBounds;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+// tutorial parameters given on the command line as `--param key=value`
+static PARAMS: RwLock<BTreeMap<String, String>> = RwLock::new(BTreeMap::new());
+
+pub fn parse_key_value(arg: &str) -> Result<(String, String), String> {
+    arg.split_once('=')
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .ok_or_else(|| format!("expected `key=value`, found `{arg}`"))
+}
+
+pub fn set(key: &str, value: &str) {
+    PARAMS
+        .write()
+        .unwrap()
+        .insert(key.to_owned(), value.to_owned());
+}
+
+pub fn all() -> Vec<(String, String)> {
+    PARAMS
+        .read()
+        .unwrap()
+        .iter()
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+// whether `value` parses as a `T`, for checking parameters before they're read
+pub fn check<T>(value: &str) -> Result<(), String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value.parse::<T>().map(|_| ()).map_err(|e| e.to_string())
+}
+
+// whether `value` parses as a `T` within `range`, the values a parameter documents
+pub fn check_range<T>(value: &str, range: impl RangeBounds<T>) -> Result<(), String>
+where
+    T: FromStr + PartialOrd,
+    T::Err: Display,
+{
+    let parsed = value.parse::<T>().map_err(|e| e.to_string())?;
+    if range.contains(&parsed) {
+        Ok(())
+    } else {
+        Err("out of range".to_owned())
+    }
+}
+
+pub fn get<T>(key: &str, default: T) -> T
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    try_get(key).unwrap_or(default)
+}
+
+pub fn try_get<T>(key: &str) -> Option<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    PARAMS.read().unwrap().get(key).map(|value| {
+        value
+            .parse()
+            .unwrap_or_else(|e| panic!("invalid value `{value}` for parameter `{key}`: {e}"))
+    })
+}
+
+// the values a parameter is swept through, from `key=from..to` or `key=a,b,c`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Sweep {
+    // evenly spaced, and whole numbers when both ends are
+    Range { from: f32, to: f32, integers: bool },
+    List(Vec<String>),
+}
+
+pub fn parse_sweep(arg: &str) -> Result<(String, Sweep), String> {
+    let (key, values) = parse_key_value(arg)?;
+    let sweep = match values.split_once("..") {
+        Some((from, to)) => {
+            let number = |end: &str| {
+                end.trim()
+                    .parse::<f32>()
+                    .map_err(|e| format!("invalid end `{end}` of the range for `{key}`: {e}"))
+            };
+            Sweep::Range {
+                from: number(from)?,
+                to: number(to)?,
+                integers: [from, to]
+                    .iter()
+                    .all(|end| end.trim().parse::<i64>().is_ok()),
+            }
+        }
+        None => Sweep::List(
+            values
+                .split(',')
+                .map(|value| value.trim().to_owned())
+                .collect(),
+        ),
+    };
+    Ok((key, sweep))
+}
+
+impl Sweep {
+    // a range gives `steps` values including both ends, without repeating any whole numbers
+    pub fn values(&self, steps: usize) -> Vec<String> {
+        match self {
+            Sweep::List(values) => values.clone(),
+            Sweep::Range { from, to, integers } => {
+                let mut values: Vec<String> = (0..steps)
+                    .map(|i| {
+                        let value = from + (to - from) * i as f32 / (steps - 1) as f32;
+                        if *integers {
+                            (value.round() as i64).to_string()
+                        } else {
+                            value.to_string()
+                        }
+                    })
+                    .collect();
+                values.dedup();
+                values
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_values_parse() {
+        assert_eq!(check::<usize>("30"), Ok(()));
+        assert!(check::<usize>("-1").is_err());
+        assert!(check::<f32>("fast").is_err());
+        assert_eq!(check_range("0.5", 0.0..=1.0), Ok(()));
+        assert!(check_range("0", 1_usize..).is_err());
+        // a float with no upper bound is still finite
+        assert!(check_range("inf", 0.0..=f32::MAX).is_err());
+        assert!(check_range("NaN", 0.0..=f32::MAX).is_err());
+    }
+
+    #[test]
+    fn sweeps_ranges_and_lists() {
+        let (key, sweep) = parse_sweep("n=4..40").unwrap();
+        assert_eq!(key, "n");
+        assert_eq!(sweep.values(5), ["4", "13", "22", "31", "40"]);
+        assert_eq!(parse_sweep("n=1..3").unwrap().1.values(9), ["1", "2", "3"]);
+        let (_, sweep) = parse_sweep("rotation=0..0.5").unwrap();
+        assert_eq!(sweep.values(3), ["0", "0.25", "0.5"]);
+        let (_, sweep) = parse_sweep("tiles=diagonals,smith").unwrap();
+        assert_eq!(sweep.values(5), ["diagonals", "smith"]);
+        assert!(parse_sweep("n=a..b").is_err());
+    }
+}
@@ -1,14 +1,15 @@
+use crate::canvas::Canvas;
 use crate::common;
+use crate::fills::{self, Fills};
+use crate::palette::{self, Palette};
+use crate::seed;
+use crate::sketch::{Param, Sketch};
 use nannou::prelude::*;
 use rand::Rng;
 
-const BLUE: (u8, u8, u8) = (19, 86, 162);
-const RED: (u8, u8, u8) = (212, 9, 32);
-const WHITE: (u8, u8, u8) = (255, 255, 255);
-const YELLOW: (u8, u8, u8) = (247, 216, 68);
-
+// the quad's corners and its colour, if it isn't left the colour of the background
 #[derive(Clone, Copy)]
-struct Quad([Point2; 2], (f32, f32, f32));
+pub struct Quad([Point2; 2], Option<Rgba>);
 
 impl Quad {
     pub fn contains_x(&self, x: f32) -> bool {
@@ -19,32 +20,40 @@ impl Quad {
     }
 }
 
-type Model = Vec<Quad>;
-
-pub fn run() {
-    nannou::app(model)
-        .event(|a, m, e| common::refresh_model_on_space(a, m, e, model))
-        .simple_window(view)
-        .size(800, 800)
-        .run();
+pub struct Model {
+    quads: Vec<Quad>,
+    size: Vec2,
 }
 
-fn color(color: &(u8, u8, u8)) -> (f32, f32, f32) {
-    let map = |component: u8| component as f32 / 255.0;
-    (map(color.0), map(color.1), map(color.2))
+pub struct PietMondrian;
+
+impl Sketch for PietMondrian {
+    type Model = Model;
+
+    const NAME: &'static str = "piet-mondrian";
+    const DESCRIPTION: &'static str = "a grid split at random, with three rectangles painted in";
+
+    fn params() -> Vec<Param> {
+        fills::PARAMS.to_vec()
+    }
+
+    fn generate(size: Vec2) -> Self::Model {
+        model(size)
+    }
+
+    fn draw(model: &Self::Model, canvas: &mut dyn Canvas) {
+        draw_model(model, canvas);
+    }
 }
 
 fn split<P: Fn(&Quad) -> bool, S: Fn(Quad) -> [Quad; 2]>(
     predicate: P,
     split_fn: S,
-    model: &mut Model,
+    model: &mut Vec<Quad>,
 ) {
     let mut new_quads = Vec::new();
-    let mut rng = rand::thread_rng();
-    loop {
-        let Some(index) = model.iter().position(&predicate) else {
-            break;
-        };
+    let mut rng = seed::rng();
+    while let Some(index) = model.iter().position(&predicate) {
         if rng.gen() {
             break;
         }
@@ -56,7 +65,7 @@ fn split<P: Fn(&Quad) -> bool, S: Fn(Quad) -> [Quad; 2]>(
     model.append(&mut new_quads);
 }
 
-fn split_quads_with(pt: &Point2, model: &mut Model) {
+fn split_quads_with(pt: &Point2, model: &mut Vec<Quad>) {
     split(|q| q.contains_x(pt.x), |q| split_quad_on_x(pt.x, q), model);
     split(|q| q.contains_y(pt.y), |q| split_quad_on_y(pt.y, q), model);
 }
@@ -75,53 +84,58 @@ fn split_quad_on_y(y: f32, quad: Quad) -> [Quad; 2] {
     ]
 }
 
-fn model(app: &App) -> Model {
-    let size = app.main_window().inner_size_points().0 * 0.9;
-    let start = -size / 2.0;
-    let mut model = vec![Quad(
-        [pt2(start, start), pt2(start + size, start + size)],
-        color(&WHITE),
-    )];
+fn model(size: Vec2) -> Model {
+    let quads = generate_quads(size);
+    Model { quads, size }
+}
+
+fn generate_quads(size: Vec2) -> Vec<Quad> {
+    let extent = size * 0.9;
+    let start = -extent / 2.0;
+    let mut model = vec![Quad([start, start + extent], None)];
     let n = 6_usize;
-    let step = size / n as f32;
+    let step = extent / n as f32;
     for i in 0..n {
-        let pt = {
-            let i = i as f32;
-            let coord = start + i * step;
-            pt2(coord, coord)
-        };
+        let pt = start + i as f32 * step;
         split_quads_with(&pt, &mut model);
     }
 
     {
-        // color three squares
-        let mut rng = rand::thread_rng();
+        // color three squares, in the palette's first three accents unless given `fill`s
+        let fills = Fills::from_params();
+        let palette = palette::current();
+        let mut rng = seed::rng();
         let len = model.len();
-        model[rng.gen_range(0..len)].1 = color(&YELLOW);
-        model[rng.gen_range(0..len)].1 = color(&RED);
-        model[rng.gen_range(0..len)].1 = color(&BLUE);
+        for accent in 0..3 {
+            let color = match &fills {
+                Some(fills) => fills.sample(&mut rng),
+                None => palette.accent(accent),
+            };
+            model[rng.gen_range(0..len)].1 = Some(color);
+        }
     }
 
     model
 }
 
-fn draw_quad(quad: &Quad, draw: &Draw) {
-    draw.quad()
-        .points(
+fn draw_quad(quad: &Quad, weight: f32, palette: &Palette, canvas: &mut dyn Canvas) {
+    canvas.polygon(
+        &[
             pt2(quad.0[0].x, quad.0[0].y),
             pt2(quad.0[1].x, quad.0[0].y),
             pt2(quad.0[1].x, quad.0[1].y),
             pt2(quad.0[0].x, quad.0[1].y),
-        )
-        .rgb(quad.1 .0, quad.1 .1, quad.1 .2)
-        .stroke_weight(5.0f32);
+        ],
+        Some(quad.1.unwrap_or(palette.background)),
+        Some((weight, palette.foreground)),
+    );
 }
 
-fn view(app: &App, m: &Model, frame: Frame) {
-    let draw = app.draw();
-    draw.background().color(nannou::color::WHITE);
-    for quad in m {
-        draw_quad(&quad, &draw);
+fn draw_model(m: &Model, canvas: &mut dyn Canvas) {
+    let palette = palette::current();
+    canvas.background(palette.background);
+    let weight = common::scaled(5.0, m.size);
+    for quad in &m.quads {
+        draw_quad(quad, weight, &palette, canvas);
     }
-    draw.to_frame(app, &frame).unwrap();
 }
@@ -0,0 +1,384 @@
+use crate::canvas::{Canvas, Inset, Pen, Raster};
+use crate::label;
+use crate::metadata::{CellMetadata, Metadata, SheetMetadata};
+use crate::page;
+use crate::palette;
+use crate::params;
+use crate::seed;
+use crate::sketch::Sketch;
+use crate::svg::Svg;
+use clap::ValueEnum;
+use nannou::prelude::*;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+pub struct Options {
+    pub frames: usize,
+    pub fps: f32,
+    // the directory for the numbered frames
+    pub out: PathBuf,
+    pub format: Format,
+    // a gif or animated png to assemble from the frames
+    pub animation: Option<PathBuf>,
+}
+
+// a grid of generations to compare, such as consecutive seeds or a parameter's values
+pub struct Sheet {
+    pub cells: Vec<Cell>,
+    pub columns: usize,
+    // the width of each thumbnail in points, which are the shape of the page
+    pub thumbnail: f32,
+    // an svg, or a png for anything else
+    pub out: PathBuf,
+}
+
+// a parameter's values, each rendered from the same seed to its own file
+pub struct Sweep {
+    pub key: String,
+    pub values: Vec<String>,
+    pub seed: u64,
+    // the directory for the files
+    pub out: PathBuf,
+    pub format: Format,
+}
+
+// the seed and any parameters a thumbnail is generated with
+pub struct Cell {
+    pub seed: u64,
+    pub params: Vec<(String, String)>,
+}
+
+// how far an animation has run, as a number of frames at a frame rate
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Time {
+    pub frame: usize,
+    pub fps: f32,
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Time {
+            frame: 0,
+            fps: 30.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Png,
+    Svg,
+}
+
+enum Animation {
+    Gif(gif::Encoder<BufWriter<File>>),
+    Apng(png::Writer<BufWriter<File>>),
+}
+
+// accepts paths which end in .gif, .png or .apng
+pub fn parse_animation_path(arg: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(arg);
+    match extension(&path).as_deref() {
+        Some("gif" | "png" | "apng") => Ok(path),
+        _ => Err(format!(
+            "expected a .gif, .png or .apng file, found `{arg}`"
+        )),
+    }
+}
+
+// accepts paths which end in .png or .svg
+pub fn parse_sheet_path(arg: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(arg);
+    match extension(&path).as_deref() {
+        Some("png" | "svg") => Ok(path),
+        _ => Err(format!("expected a .png or .svg file, found `{arg}`")),
+    }
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+}
+
+impl Animation {
+    fn create(path: &Path, options: &Options, width: u32, height: u32) -> Result<Self, String> {
+        let is_gif = extension(path).as_deref() == Some("gif");
+        // gif sizes are 16 bit
+        let max = u16::MAX as u32;
+        if is_gif && (width > max || height > max) {
+            return Err(format!(
+                "{width}x{height} pixels is too large for a gif, which is at most {max} pixels across"
+            ));
+        }
+        let file = BufWriter::new(
+            File::create(path).map_err(|e| format!("failed to create {}: {e}", path.display()))?,
+        );
+        let error = |e: &dyn std::fmt::Display| format!("failed to write {}: {e}", path.display());
+        if is_gif {
+            let mut encoder =
+                gif::Encoder::new(file, width as u16, height as u16, &[]).map_err(|e| error(&e))?;
+            encoder
+                .set_repeat(gif::Repeat::Infinite)
+                .map_err(|e| error(&e))?;
+            Ok(Animation::Gif(encoder))
+        } else {
+            let mut encoder = png::Encoder::new(file, width, height);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            encoder
+                .set_animated(options.frames as u32, 0)
+                .and_then(|_| encoder.set_frame_delay(100, (options.fps * 100.0).round() as u16))
+                .map_err(|e| error(&e))?;
+            Ok(Animation::Apng(
+                encoder.write_header().map_err(|e| error(&e))?,
+            ))
+        }
+    }
+
+    fn write(&mut self, raster: &Raster, fps: f32) -> Result<(), String> {
+        let mut pixels = raster.rgba();
+        match self {
+            Animation::Gif(encoder) => {
+                let mut frame = gif::Frame::from_rgba_speed(
+                    raster.width() as u16,
+                    raster.height() as u16,
+                    &mut pixels,
+                    10,
+                );
+                // gif frame delays are in hundredths of a second
+                frame.delay = (100.0 / fps).round() as u16;
+                encoder.write_frame(&frame).map_err(|e| e.to_string())
+            }
+            Animation::Apng(writer) => writer.write_image_data(&pixels).map_err(|e| e.to_string()),
+        }
+    }
+
+    fn finish(self) -> Result<(), String> {
+        match self {
+            Animation::Gif(encoder) => {
+                // the trailer is written when the encoder is dropped
+                drop(encoder);
+                Ok(())
+            }
+            Animation::Apng(writer) => writer.finish().map_err(|e| e.to_string()),
+        }
+    }
+}
+
+// renders frames of a sketch on the cpu, advancing the model between them
+// when it is animated, or regenerating it for every frame when it is not
+pub fn record<S: Sketch>(options: &Options) -> Result<(), String> {
+    std::fs::create_dir_all(&options.out)
+        .map_err(|e| format!("failed to create {}: {e}", options.out.display()))?;
+    let page = page::current();
+    let (size, scale) = (page.size, page.pixels_per_point());
+    let artwork = page::artwork(size);
+    let mut model = S::generate(artwork);
+    let mut animation = None;
+    for frame in 0..options.frames {
+        if frame > 0 {
+            if S::animated() {
+                S::update(&mut model, 1.0 / options.fps);
+            } else {
+                seed::next();
+                model = S::generate(artwork);
+            }
+        }
+        // still tutorials start again from each frame's seed
+        let time = Time {
+            frame: if S::animated() { frame } else { 0 },
+            fps: options.fps,
+        };
+        let metadata = Metadata::current(S::NAME, &S::params(), time);
+        if options.format == Format::Svg {
+            let mut svg = Svg::new(size.x, size.y, page.units);
+            draw_page(&mut svg, artwork, |canvas| S::draw(&model, canvas));
+            let path = options.out.join(format!("{frame:04}.svg"));
+            svg.save(&path, Some(&metadata))?;
+            metadata.write_sidecar(&path)?;
+        }
+        // animations are assembled from rasters whichever format the frames are saved in
+        if options.format == Format::Svg && options.animation.is_none() {
+            continue;
+        }
+        let mut raster = Raster::new(size.x, size.y, scale)?;
+        draw_page(&mut raster, artwork, |canvas| S::draw(&model, canvas));
+        if options.format == Format::Png {
+            let path = options.out.join(format!("{frame:04}.png"));
+            raster.save(&path, Some(&metadata))?;
+            metadata.write_sidecar(&path)?;
+        }
+        if let Some(path) = &options.animation {
+            let animation = match &mut animation {
+                Some(animation) => animation,
+                None => animation.insert(Animation::create(
+                    path,
+                    options,
+                    raster.width(),
+                    raster.height(),
+                )?),
+            };
+            animation
+                .write(&raster, options.fps)
+                .map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+        }
+    }
+    if let (Some(animation), Some(path)) = (animation, &options.animation) {
+        animation
+            .finish()
+            .map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+    }
+    Ok(())
+}
+
+// renders the sheet's thumbnails in rows from the top left, each generated at full size
+// and then scaled down, so that any one can be rendered again with its seed and parameters
+pub fn contact_sheet<S: Sketch>(sheet: &Sheet) -> Result<(), String> {
+    let columns = sheet.columns.max(1);
+    let rows = sheet.cells.len().div_ceil(columns).max(1);
+    let page = page::current().size;
+    let thumbnail = vec2(sheet.thumbnail, sheet.thumbnail * page.y / page.x);
+    let gap = sheet.thumbnail * 0.05;
+    let step = thumbnail + Vec2::splat(gap);
+    let (width, height) = (columns as f32 * step.x + gap, rows as f32 * step.y + gap);
+    let metadata = Metadata {
+        sheet: Some(SheetMetadata {
+            columns,
+            thumbnail: sheet.thumbnail,
+            cells: sheet
+                .cells
+                .iter()
+                .map(|cell| CellMetadata {
+                    seed: cell.seed,
+                    params: cell.params.iter().cloned().collect(),
+                })
+                .collect(),
+        }),
+        ..Metadata::current(S::NAME, &S::params(), Time::default())
+    };
+    // the pen draws on the sheet rather than in each thumbnail, so it keeps its width in print
+    save(&sheet.out, vec2(width, height), Some(&metadata), |canvas| {
+        with_pen(canvas, |canvas| {
+            canvas.background(palette::current().background);
+            for (i, cell) in sheet.cells.iter().enumerate() {
+                seed::set(cell.seed);
+                for (key, value) in &cell.params {
+                    params::set(key, value);
+                }
+                let artwork = page::artwork(page);
+                let model = S::generate(artwork);
+                let (row, column) = (i / columns, i % columns);
+                let centre = pt2(
+                    -width / 2.0 + gap + thumbnail.x / 2.0 + column as f32 * step.x,
+                    height / 2.0 - gap - thumbnail.y / 2.0 - row as f32 * step.y,
+                );
+                let mut tile = Inset::tile(canvas, page, sheet.thumbnail / page.x, centre);
+                label::frame(&mut tile, artwork, |canvas| S::draw(&model, canvas));
+            }
+        })
+    })?;
+    metadata.write_sidecar(&sheet.out)
+}
+
+// renders a file for each of the sweep's values, numbered and named after the value
+pub fn sweep<S: Sketch>(sweep: &Sweep) -> Result<(), String> {
+    std::fs::create_dir_all(&sweep.out)
+        .map_err(|e| format!("failed to create {}: {e}", sweep.out.display()))?;
+    let extension = match sweep.format {
+        Format::Png => "png",
+        Format::Svg => "svg",
+    };
+    for (i, value) in sweep.values.iter().enumerate() {
+        seed::set(sweep.seed);
+        params::set(&sweep.key, value);
+        let name = file_name(&format!("{}={value}", sweep.key));
+        still::<S>(
+            &sweep.out.join(format!("{i:02}-{name}.{extension}")),
+            Time::default(),
+        )?;
+    }
+    Ok(())
+}
+
+// values such as `image:<file>` can't go in a file name as they are
+fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || "=.-_".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+// renders one generation with the current seed and parameters, advanced to `time`
+// when it is animated, with its metadata embedded and beside it
+pub fn still<S: Sketch>(path: &Path, time: Time) -> Result<(), String> {
+    let size = page::current().size;
+    let artwork = page::artwork(size);
+    let mut model = S::generate(artwork);
+    if S::animated() {
+        for _ in 0..time.frame {
+            S::update(&mut model, 1.0 / time.fps);
+        }
+    }
+    let metadata = Metadata::current(S::NAME, &S::params(), time);
+    save(path, size, Some(&metadata), |canvas| {
+        draw_page(canvas, artwork, |canvas| S::draw(&model, canvas))
+    })?;
+    metadata.write_sidecar(path)
+}
+
+// draws the artwork in its label's frame, with every stroke the page's pen when it has one
+fn draw_page(canvas: &mut dyn Canvas, artwork: Vec2, draw: impl FnOnce(&mut dyn Canvas)) {
+    with_pen(canvas, |canvas| label::frame(canvas, artwork, draw));
+}
+
+fn with_pen(canvas: &mut dyn Canvas, draw: impl FnOnce(&mut dyn Canvas)) {
+    match page::current().pen {
+        Some(width) => draw(&mut Pen::new(canvas, width)),
+        None => draw(canvas),
+    }
+}
+
+// saves an svg, or a png for anything else, in the page's units and resolution
+fn save(
+    path: &Path,
+    size: Vec2,
+    metadata: Option<&Metadata>,
+    draw: impl FnOnce(&mut dyn Canvas),
+) -> Result<(), String> {
+    let page = page::current();
+    if extension(path).as_deref() == Some("svg") {
+        let mut svg = Svg::new(size.x, size.y, page.units);
+        draw(&mut svg);
+        svg.save(path, metadata)
+    } else {
+        let mut raster = Raster::new(size.x, size.y, page.pixels_per_point())?;
+        draw(&mut raster);
+        raster.save(path, metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gifs_larger_than_16_bits_are_errors() {
+        let options = Options {
+            frames: 1,
+            fps: 30.0,
+            out: PathBuf::from("frames"),
+            format: Format::Png,
+            animation: None,
+        };
+        // the size is checked before the file is created
+        let path = Path::new("too-large.gif");
+        assert!(Animation::create(path, &options, 70_000, 10).is_err());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,70 @@
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use std::sync::Mutex;
+
+// the seed of the current generation and the random numbers drawn from it so far,
+// so that any generation can be repeated with `--seed`
+static STATE: Mutex<Option<(u64, StdRng)>> = Mutex::new(None);
+
+// draws from the current generation's random numbers
+pub struct Seeded;
+
+pub fn rng() -> Seeded {
+    Seeded
+}
+
+// starts a generation over from the beginning of `seed`'s random numbers
+pub fn set(seed: u64) {
+    *STATE.lock().unwrap() = Some((seed, StdRng::seed_from_u64(seed)));
+}
+
+pub fn current() -> u64 {
+    with_state(|(seed, _)| *seed)
+}
+
+// moves on to the following seed, for the next generation
+pub fn next() {
+    set(current().wrapping_add(1));
+}
+
+fn with_state<T>(f: impl FnOnce(&mut (u64, StdRng)) -> T) -> T {
+    let mut state = STATE.lock().unwrap();
+    f(state.get_or_insert_with(|| {
+        let seed = rand::thread_rng().gen::<u32>() as u64;
+        (seed, StdRng::seed_from_u64(seed))
+    }))
+}
+
+impl RngCore for Seeded {
+    fn next_u32(&mut self) -> u32 {
+        with_state(|(_, rng)| rng.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        with_state(|(_, rng)| rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        with_state(|(_, rng)| rng.fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        with_state(|(_, rng)| rng.try_fill_bytes(dest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeds_repeat_their_numbers() {
+        set(7);
+        let first: Vec<u32> = (0..4).map(|_| rng().gen()).collect();
+        next();
+        assert_eq!(current(), 8);
+        set(7);
+        let again: Vec<u32> = (0..4).map(|_| rng().gen()).collect();
+        assert_eq!(first, again);
+    }
+}
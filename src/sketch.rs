@@ -0,0 +1,222 @@
+use crate::canvas::Canvas;
+use crate::circle_packing::CirclePacking;
+use crate::common;
+use crate::cubic_disarray::CubicDisarray;
+use crate::hours_of_dark::HoursOfDark;
+use crate::hypnotic_squares::HypnoticSquares;
+use crate::joy_division::JoyDivision;
+use crate::label;
+use crate::piet_mondrian::PietMondrian;
+use crate::record;
+use crate::seed;
+use crate::tiled_lines::TiledLines;
+use crate::triangular_mesh::TriangularMesh;
+use crate::un_deux_trois::UnDeuxTrois;
+use clap::builder::PossibleValue;
+use nannou::prelude::*;
+use std::marker::PhantomData;
+
+// a parameter a sketch reads from `--param name=value`
+#[derive(Clone, Copy, Debug)]
+pub struct Param {
+    pub name: &'static str,
+    pub default: &'static str,
+    // the values or range it accepts
+    pub values: &'static str,
+    pub description: &'static str,
+    // checks a given value parses as the parameter's type
+    pub parse: fn(&str) -> Result<(), String>,
+}
+
+impl Param {
+    // a line describing the parameter, such as for a listing
+    pub fn summary(&self) -> String {
+        format!(
+            "  {}: {} ({}, default {})",
+            self.name, self.description, self.values, self.default
+        )
+    }
+}
+
+// a tutorial, which generates a model of its artwork for a square canvas and draws it
+pub trait Sketch: 'static {
+    type Model: 'static;
+
+    // the name it's run by on the command line
+    const NAME: &'static str;
+    const DESCRIPTION: &'static str;
+
+    fn params() -> Vec<Param>;
+    fn generate(size: Vec2) -> Self::Model;
+    fn draw(model: &Self::Model, canvas: &mut dyn Canvas);
+
+    // animated sketches are advanced between recorded frames,
+    // while still ones are regenerated for each of them
+    fn animated() -> bool {
+        false
+    }
+
+    // moves the model on in the window, or between an animation's frames
+    fn update(_model: &mut Self::Model, _seconds: f32) {}
+
+    // the next generation, when Space is pressed
+    fn regenerate(model: &mut Self::Model, size: Vec2) {
+        *model = Self::generate(size);
+    }
+
+    // fits the model to a resized window, by default generating the same piece again at the new size
+    fn resize(model: &mut Self::Model, size: Vec2) {
+        seed::set(seed::current());
+        *model = Self::generate(size);
+    }
+
+    // responds to any other input in the window
+    fn event(_app: &App, _model: &mut Self::Model, _event: &Event) {}
+}
+
+// a sketch with its model's type erased, so that sketches can be listed together
+pub struct Entry {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub params: fn() -> Vec<Param>,
+    pub run: fn(),
+    pub record: fn(&record::Options) -> Result<(), String>,
+    pub contact_sheet: fn(&record::Sheet) -> Result<(), String>,
+    pub sweep: fn(&record::Sweep) -> Result<(), String>,
+    pub still: fn(&std::path::Path, record::Time) -> Result<(), String>,
+    // generates a model for a window which switches between sketches
+    pub start: fn(Vec2) -> Box<dyn Instance>,
+}
+
+const fn entry<S: Sketch>() -> Entry {
+    Entry {
+        name: S::NAME,
+        description: S::DESCRIPTION,
+        params: S::params,
+        run: run::<S>,
+        record: record::record::<S>,
+        contact_sheet: record::contact_sheet::<S>,
+        sweep: record::sweep::<S>,
+        still: record::still::<S>,
+        start: start::<S>,
+    }
+}
+
+// a generated model with its sketch's type erased, so that one window can show any sketch
+pub trait Instance {
+    fn draw(&self, canvas: &mut dyn Canvas);
+    fn update(&mut self, seconds: f32);
+    fn regenerate(&mut self, size: Vec2);
+    fn resize(&mut self, size: Vec2);
+    fn event(&mut self, app: &App, event: &Event);
+}
+
+struct Generated<S: Sketch> {
+    model: S::Model,
+    sketch: PhantomData<S>,
+}
+
+impl<S: Sketch> Instance for Generated<S> {
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        S::draw(&self.model, canvas);
+    }
+
+    fn update(&mut self, seconds: f32) {
+        S::update(&mut self.model, seconds);
+    }
+
+    fn regenerate(&mut self, size: Vec2) {
+        S::regenerate(&mut self.model, size);
+    }
+
+    fn resize(&mut self, size: Vec2) {
+        S::resize(&mut self.model, size);
+    }
+
+    fn event(&mut self, app: &App, event: &Event) {
+        S::event(app, &mut self.model, event);
+    }
+}
+
+fn start<S: Sketch>(size: Vec2) -> Box<dyn Instance> {
+    Box::new(Generated::<S> {
+        model: S::generate(size),
+        sketch: PhantomData,
+    })
+}
+
+// every tutorial, in the order of the series
+pub const SKETCHES: &[Entry] = &[
+    entry::<TiledLines>(),
+    entry::<JoyDivision>(),
+    entry::<CubicDisarray>(),
+    entry::<TriangularMesh>(),
+    entry::<UnDeuxTrois>(),
+    entry::<CirclePacking>(),
+    entry::<HypnoticSquares>(),
+    entry::<PietMondrian>(),
+    entry::<HoursOfDark>(),
+];
+
+// the sketches' names for the command line, with their descriptions as help
+pub fn possible_values() -> impl Iterator<Item = PossibleValue> {
+    SKETCHES
+        .iter()
+        .map(|entry| PossibleValue::new(entry.name).help(entry.description))
+}
+
+pub fn find(name: &str) -> Option<&'static Entry> {
+    SKETCHES.iter().find(|entry| entry.name == name)
+}
+
+// opens a window on the sketch, which Space regenerates and resizing fits to the window
+fn run<S: Sketch>() {
+    let (width, height) = common::page_size();
+    nannou::app(|app| start::<S>(common::artwork_size(app)))
+        .update(|_, instance, update| instance.update(update.since_last.as_secs_f32()))
+        .event(|app, instance, event| {
+            self::event(app, instance.as_mut(), &event);
+            if common::space_released(&event) {
+                seed::next();
+                instance.regenerate(common::artwork_size(app));
+            }
+        })
+        .simple_window(|app, instance, frame| view(app, instance.as_ref(), frame))
+        .size(width, height)
+        .run();
+}
+
+// passes a window's event on to the instance, and fits it to the window when that is resized
+pub fn event(app: &App, instance: &mut dyn Instance, event: &Event) {
+    instance.event(app, event);
+    let size = common::artwork_size(app);
+    // a minimised window has nothing to fit the artwork to
+    if common::resized(event) && size.min_element() > 0.0 {
+        instance.resize(size);
+    }
+}
+
+pub fn view(app: &App, instance: &dyn Instance, frame: Frame) {
+    let mut draw = app.draw();
+    label::frame(&mut draw, common::artwork_size(app), |canvas| {
+        instance.draw(canvas)
+    });
+    draw.to_frame(app, &frame).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn names_and_params_are_unique() {
+        let names: HashSet<_> = SKETCHES.iter().map(|entry| entry.name).collect();
+        assert_eq!(names.len(), SKETCHES.len());
+        for entry in SKETCHES {
+            let params = (entry.params)();
+            let unique: HashSet<_> = params.iter().map(|param| param.name).collect();
+            assert_eq!(unique.len(), params.len(), "{}", entry.name);
+        }
+    }
+}
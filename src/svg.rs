@@ -0,0 +1,159 @@
+use crate::canvas::{Canvas, Caps, Outline};
+use crate::metadata::Metadata;
+use crate::page::Units;
+use crate::palette;
+use nannou::prelude::*;
+use std::fmt::Write;
+use std::path::Path;
+
+// renders to scalable vector graphics, with text as filled outlines so that
+// files open the same everywhere and plotters can trace them
+pub struct Svg {
+    width: f32,
+    height: f32,
+    // the units of the document's size, with the drawing in points within its view box
+    units: Units,
+    elements: String,
+}
+
+impl Svg {
+    pub fn new(width: f32, height: f32, units: Units) -> Self {
+        Svg {
+            width,
+            height,
+            units,
+            elements: String::new(),
+        }
+    }
+
+    pub fn save(&self, path: &Path, metadata: Option<&Metadata>) -> Result<(), String> {
+        let metadata = metadata
+            .map(|metadata| format!("{}\n", metadata.svg_element()))
+            .unwrap_or_default();
+        let document = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {w} {h}\">\n{metadata}{}</svg>\n",
+            self.units.format(self.width),
+            self.units.format(self.height),
+            self.elements,
+            w = self.width,
+            h = self.height,
+        );
+        std::fs::write(path, document)
+            .map_err(|e| format!("failed to write {}: {e}", path.display()))
+    }
+
+    // svg's origin is at the top left with y pointing down
+    fn point(&self, pt: Point2) -> String {
+        format!("{},{}", pt.x + self.width / 2.0, self.height / 2.0 - pt.y)
+    }
+
+    fn points(&self, points: &[Point2]) -> String {
+        points
+            .iter()
+            .map(|pt| self.point(*pt))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn element(&mut self, element: std::fmt::Arguments) {
+        writeln!(self.elements, "  {element}").unwrap();
+    }
+}
+
+fn fill(fill: Option<Rgba>) -> String {
+    match fill {
+        Some(fill) if fill.alpha < 1.0 => {
+            format!(
+                "fill=\"{}\" fill-opacity=\"{}\"",
+                palette::hex(fill),
+                fill.alpha
+            )
+        }
+        Some(fill) => format!("fill=\"{}\"", palette::hex(fill)),
+        None => "fill=\"none\"".to_owned(),
+    }
+}
+
+fn stroke(outline: Option<Outline>, caps: Caps) -> String {
+    let Some((weight, stroke)) = outline else {
+        return String::new();
+    };
+    let caps = match caps {
+        Caps::Butt => "butt",
+        Caps::Round => "round",
+        Caps::Square => "square",
+    };
+    let opacity = if stroke.alpha < 1.0 {
+        format!(" stroke-opacity=\"{}\"", stroke.alpha)
+    } else {
+        String::new()
+    };
+    format!(
+        " stroke=\"{}\" stroke-width=\"{weight}\" stroke-linecap=\"{caps}\"{opacity}",
+        palette::hex(stroke)
+    )
+}
+
+impl Canvas for Svg {
+    fn background(&mut self, color: Rgba) {
+        self.elements.clear();
+        let (width, height) = (self.width, self.height);
+        self.element(format_args!(
+            "<rect width=\"{width}\" height=\"{height}\" {}/>",
+            fill(Some(color))
+        ));
+    }
+
+    fn line(&mut self, start: Point2, end: Point2, weight: f32, caps: Caps, color: Rgba) {
+        let points = self.points(&[start, end]);
+        self.element(format_args!(
+            "<polyline points=\"{points}\" fill=\"none\"{}/>",
+            stroke(Some((weight, color)), caps)
+        ));
+    }
+
+    fn polyline(&mut self, points: &[Point2], weight: f32, color: Rgba) {
+        let points = self.points(points);
+        self.element(format_args!(
+            "<polyline points=\"{points}\" fill=\"none\"{}/>",
+            stroke(Some((weight, color)), Caps::Butt)
+        ));
+    }
+
+    fn polygon(&mut self, points: &[Point2], fill: Option<Rgba>, outline: Option<Outline>) {
+        let points = self.points(points);
+        self.element(format_args!(
+            "<polygon points=\"{points}\" {}{}/>",
+            self::fill(fill),
+            stroke(outline, Caps::Butt)
+        ));
+    }
+
+    fn ellipse(
+        &mut self,
+        centre: Point2,
+        radius: f32,
+        fill: Option<Rgba>,
+        outline: Option<Outline>,
+    ) {
+        let (x, y) = (centre.x + self.width / 2.0, self.height / 2.0 - centre.y);
+        self.element(format_args!(
+            "<circle cx=\"{x}\" cy=\"{y}\" r=\"{radius}\" {}{}/>",
+            self::fill(fill),
+            stroke(outline, Caps::Butt)
+        ));
+    }
+
+    fn shape(&mut self, contours: &[Vec<Point2>], fill: Rgba) {
+        let data = contours
+            .iter()
+            .filter(|contour| !contour.is_empty())
+            .map(|contour| format!("M{}Z", self.points(contour).replace(' ', "L")))
+            .collect::<Vec<_>>()
+            .join("");
+        self.element(format_args!(
+            "<path d=\"{data}\" fill-rule=\"evenodd\" {}/>",
+            self::fill(Some(fill))
+        ));
+    }
+}
@@ -0,0 +1,65 @@
+use crate::canvas::Canvas;
+use nannou::lyon::path::iterator::PathIterator;
+use nannou::lyon::path::PathEvent;
+use nannou::prelude::*;
+
+// glyphs are laid out at this size and then scaled, as nannou only takes whole point sizes
+const LAYOUT_SIZE: u32 = 64;
+
+// how far a flattened curve may stray from the glyph's outline, in points at the layout size
+const TOLERANCE: f32 = 0.5;
+
+// the outlines of a line of text in nannou's default font, `size` points high
+// and centred on `position`, as closed contours to be filled even-odd
+pub fn outlines(text: &str, position: Point2, size: f32) -> Vec<Vec<Point2>> {
+    let layout = nannou::text::text(text)
+        .font_size(LAYOUT_SIZE)
+        .no_line_wrap()
+        .center_justify()
+        .align_middle_y()
+        .build(geom::Rect::from_w_h(
+            LAYOUT_SIZE as f32 * (text.chars().count() + 1) as f32,
+            LAYOUT_SIZE as f32 * 2.0,
+        ));
+    let scale = size / LAYOUT_SIZE as f32;
+    let mut contours = Vec::new();
+    let mut contour = Vec::new();
+    for event in layout.path_events().flattened(TOLERANCE) {
+        let point = |pt: nannou::lyon::math::Point| position + vec2(pt.x, pt.y) * scale;
+        match event {
+            PathEvent::Begin { at } => contour = vec![point(at)],
+            PathEvent::Line { to, .. } => contour.push(point(to)),
+            PathEvent::End { .. } => contours.push(std::mem::take(&mut contour)),
+            // flattening leaves only straight lines
+            PathEvent::Quadratic { .. } | PathEvent::Cubic { .. } => unreachable!(),
+        }
+    }
+    contours
+}
+
+// as `outlines`, shrunk about `position` if need be to fit within `width`
+pub fn outlines_within(text: &str, position: Point2, size: f32, width: f32) -> Vec<Vec<Point2>> {
+    let contours = outlines(text, position, size);
+    let extent = contours
+        .iter()
+        .flatten()
+        .map(|pt| (pt.x - position.x).abs() * 2.0)
+        .fold(0.0, f32::max);
+    if extent <= width {
+        return contours;
+    }
+    let scale = width / extent;
+    contours
+        .into_iter()
+        .map(|contour| {
+            contour
+                .into_iter()
+                .map(|pt| position + (pt - position) * scale)
+                .collect()
+        })
+        .collect()
+}
+
+pub fn draw(canvas: &mut dyn Canvas, text: &str, position: Point2, size: f32, color: Rgba) {
+    canvas.shape(&outlines(text, position, size), color);
+}
@@ -1,71 +1,97 @@
+use crate::canvas::Canvas;
 use crate::common;
-use itertools::Itertools;
+use crate::fills::{self, Fills};
+use crate::layout;
+use crate::palette;
+use crate::params;
+use crate::sketch::{Param, Sketch};
+use crate::truchet::{self, Placement, TileSet};
+use crate::tween::{self, Tweened};
 use nannou::prelude::*;
-use rand::Rng;
 
-#[derive(PartialEq)]
-enum Direction {
-    Forwards,
-    Backwards,
-}
+type Lines = Vec<Placement>;
 
-struct Line {
-    x: f32,
-    y: f32,
-    step: f32,
-    direction: Direction,
+pub struct Model {
+    lines: Tweened<Placement>,
+    size: Vec2,
 }
 
-pub fn run() {
-    nannou::app(generate_lines)
-        .event(|a, m, e| common::refresh_model_on_space(a, m, e, generate_lines))
-        .simple_window(view)
-        .size(800, 800)
-        .run();
-}
+const PARAMS: &[Param] = &[
+    Param {
+        name: "tiles",
+        default: "diagonals",
+        values: "diagonals, smith, triangles, multi-scale",
+        description: "the set of truchet tiles to place",
+        parse: params::check::<TileSet>,
+    },
+    Param {
+        name: "n",
+        default: "30",
+        values: "1 or more",
+        description: "the number of tiles across the grid",
+        parse: |value| params::check_range(value, 1_usize..),
+    },
+];
 
-fn draw_line(line: &Line, draw: &Draw) {
-    let (start, end) = match line.direction {
-        Direction::Forwards => (
-            pt2(line.x, line.y),
-            pt2(line.x + line.step, line.y + line.step),
-        ),
-        Direction::Backwards => (
-            pt2(line.x + line.step, line.y),
-            pt2(line.x, line.y + line.step),
-        ),
-    };
-    draw.line()
-        .start(start)
-        .end(end)
-        .weight(2_f32)
-        .caps_square();
-}
+pub struct TiledLines;
+
+impl Sketch for TiledLines {
+    type Model = Model;
+
+    const NAME: &'static str = "tiled-lines";
+    const DESCRIPTION: &'static str = "a grid of randomly turned truchet tiles";
+
+    fn params() -> Vec<Param> {
+        [PARAMS, layout::PARAMS, fills::PARAMS, tween::PARAMS].concat()
+    }
+
+    fn generate(size: Vec2) -> Self::Model {
+        Model {
+            lines: Tweened::new(generate_lines(size)),
+            size,
+        }
+    }
 
-fn generate_lines(app: &App) -> Vec<Line> {
-    let n = 30;
-    let width = app.main_window().inner_size_points().0;
-    let step = width / (n as f32);
-    let start = -width / 2_f32;
-    let mut rng = rand::thread_rng();
-    let mut ret = Vec::new();
-    for (i, j) in (0..n).cartesian_product(0..n) {
-        use Direction::*;
-        ret.push(Line {
-            x: start + (i as f32) * step,
-            y: start + (j as f32) * step,
-            step,
-            direction: if rng.gen() { Forwards } else { Backwards },
-        })
+    fn draw(model: &Self::Model, canvas: &mut dyn Canvas) {
+        draw_model(&model.lines.current(), model.size, canvas);
     }
-    ret
+
+    fn update(model: &mut Self::Model, seconds: f32) {
+        model.lines.advance(seconds);
+    }
+
+    fn regenerate(model: &mut Self::Model, size: Vec2) {
+        model.lines.retarget(generate_lines(size));
+        model.size = size;
+    }
+
+    // the same tiles, scaled to fit the resized window
+    fn resize(model: &mut Self::Model, size: Vec2) {
+        let factor = common::fit(model.size, size);
+        model.lines.map(|placement| Placement {
+            cell: placement.cell.scaled(factor),
+            ..placement.clone()
+        });
+        model.size = size;
+    }
+}
+
+fn generate_lines(size: Vec2) -> Lines {
+    let n = params::get("n", 30_usize).max(1);
+    let tiles = params::get("tiles", TileSet::diagonals());
+    let fills = Fills::from_params();
+    truchet::place(
+        &tiles,
+        &layout::cells(-size / 2_f32, size, n),
+        fills.as_ref(),
+    )
 }
 
-fn view(app: &App, model: &Vec<Line>, frame: Frame) {
-    let draw = app.draw();
-    draw.background().color(WHITE);
-    for line in model {
-        draw_line(line, &draw);
+fn draw_model(lines: &Lines, size: Vec2, canvas: &mut dyn Canvas) {
+    let palette = palette::current();
+    canvas.background(palette.background);
+    for placement in lines {
+        let weight = common::scaled(2.0, size) * placement.cell.scale;
+        truchet::draw_placement(placement, canvas, weight, palette.foreground);
     }
-    draw.to_frame(app, &frame).unwrap();
 }
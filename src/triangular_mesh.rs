@@ -1,49 +1,83 @@
+use crate::canvas::Canvas;
 use crate::common;
+use crate::fills::{self, Fills};
+use crate::palette::{self, Palette};
+use crate::seed;
+use crate::sketch::{Param, Sketch};
 use nannou::{color::Component, prelude::*};
 use rand::Rng;
 
 type Coord = (usize, usize);
 type Grid = Vec<Vec<(Point2, Coord)>>;
-type ColorMap = std::collections::HashMap<(Coord, Coord, Coord), f32>;
-type Model = (Grid, ColorMap);
+type ColorMap = std::collections::HashMap<(Coord, Coord, Coord), Rgba>;
+pub struct Model {
+    grid: Grid,
+    color_map: ColorMap,
+    size: Vec2,
+}
+
+pub struct TriangularMesh;
+
+impl Sketch for TriangularMesh {
+    type Model = Model;
+
+    const NAME: &'static str = "triangular-mesh";
+    const DESCRIPTION: &'static str = "a jittered grid of shaded triangles";
+
+    fn params() -> Vec<Param> {
+        fills::PARAMS.to_vec()
+    }
+
+    fn generate(size: Vec2) -> Self::Model {
+        generate_model(size)
+    }
 
-pub fn run() {
-    nannou::app(generate_model)
-        .event(|a, m, e| common::refresh_model_on_space(a, m, e, generate_model))
-        .simple_window(view)
-        .size(800, 800)
-        .run();
+    fn draw(model: &Self::Model, canvas: &mut dyn Canvas) {
+        draw_model(model, canvas);
+    }
 }
 
-fn generate_model(app: &App) -> Model {
-    let grid = generate_grid(&app);
+fn generate_model(size: Vec2) -> Model {
+    let grid = generate_grid(size);
     let color_map = generate_color_map(&grid);
-    (grid, color_map)
+    Model {
+        grid,
+        color_map,
+        size,
+    }
 }
 
 fn key(triangle: &[(Point2, Coord); 3]) -> (Coord, Coord, Coord) {
     (triangle[0].1, triangle[1].1, triangle[2].1)
 }
 
+// random grays between the foreground and background, unless given perceptual `fill`s
 fn generate_color_map(grid: &Grid) -> ColorMap {
     let mut map = ColorMap::new();
-    let mut rng = rand::thread_rng();
-    for_each_triangle(&grid, |triangle| {
-        map.insert(key(triangle), rng.gen_range(0.0..f32::max_intensity()));
+    let fills = Fills::from_params();
+    let palette = palette::current();
+    let mut rng = seed::rng();
+    for_each_triangle(grid, |triangle| {
+        let color = match &fills {
+            Some(fills) => fills.sample(&mut rng),
+            None => palette.shade(1.0 - rng.gen_range(0.0..f32::max_intensity())),
+        };
+        map.insert(key(triangle), color);
     });
     map
 }
 
-fn generate_grid(app: &App) -> Grid {
-    let n = 7_usize;
-    let size = app.main_window().inner_size_points().0 * 0.9;
-    let xstart = -size / 2.0;
-    let xstep = size / n as f32;
+fn generate_grid(size: Vec2) -> Grid {
+    let extent = size * 0.9;
+    // seven points across the shorter side, or as many as keep the triangles near even
+    let n = (7.0 * extent.x / extent.min_element()).round() as usize;
+    let xstart = -extent.x / 2.0;
+    let xstep = extent.x / n as f32;
     let ystep = xstep * 3.0.sqrt() * 0.5;
-    let m = (size / ystep).floor() as usize + 1;
+    let m = (extent.y / ystep).floor() as usize + 1;
     let ystart = -((m - 1) as f32 * ystep) * 0.5;
     let mut grid = Vec::new();
-    let mut rng = rand::thread_rng();
+    let mut rng = seed::rng();
     for j in 0..m {
         let mut row = Vec::new();
         for i in 0..n {
@@ -99,19 +133,23 @@ fn for_each_triangle<F: FnMut(&[(Point2, (usize, usize)); 3])>(grid: &Grid, mut
     }
 }
 
-fn draw_triangle(triangle: &[(Point2, (usize, usize)); 3], draw: &Draw, color_map: &ColorMap) {
-    draw.polygon()
-        .rgba(0.0, 0.0, 0.0, 0.0)
-        .stroke_weight(2.0)
-        .gray(color_map[&key(&triangle)])
-        .points(triangle.iter().map(|p| p.0));
+fn draw_triangle(
+    triangle: &[(Point2, (usize, usize)); 3],
+    canvas: &mut dyn Canvas,
+    model: &Model,
+    palette: &Palette,
+) {
+    canvas.polygon(
+        &triangle.map(|p| p.0),
+        Some(model.color_map[&key(triangle)]),
+        Some((common::scaled(2.0, model.size), palette.foreground)),
+    );
 }
 
-fn view(app: &App, model: &Model, frame: Frame) {
-    let draw = app.draw();
-    draw.background().color(WHITE);
-    for_each_triangle(&model.0, |points| {
-        draw_triangle(points, &draw, &model.1);
+fn draw_model(model: &Model, canvas: &mut dyn Canvas) {
+    let palette = palette::current();
+    canvas.background(palette.background);
+    for_each_triangle(&model.grid, |points| {
+        draw_triangle(points, canvas, model, &palette);
     });
-    draw.to_frame(app, &frame).unwrap();
 }
@@ -0,0 +1,343 @@
+use crate::canvas::{Canvas, Caps};
+use crate::fills::Fills;
+use crate::layout::Cell;
+use crate::seed;
+use crate::tween::{self, Tween};
+use nannou::prelude::*;
+use rand::Rng;
+
+const ARC_RESOLUTION: usize = 16;
+
+// a mark on a tile, in unit space with the origin at the bottom left
+#[derive(Clone, Debug, PartialEq)]
+pub enum Stroke {
+    Line(Point2, Point2),
+    // angles are in radians, anticlockwise from the x-axis
+    Arc {
+        centre: Point2,
+        radius: f32,
+        start: f32,
+        end: f32,
+    },
+    Fill(Vec<Point2>),
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Tile(pub Vec<Stroke>);
+
+pub struct TileSet(Vec<Tile>);
+
+#[derive(Clone)]
+pub struct Placement {
+    pub cell: Cell,
+    pub tile: Tile,
+    // the colour of the tile's filled strokes, when it isn't drawn in the foreground
+    pub fill: Option<Rgba>,
+}
+
+// a quarter turn anticlockwise about the centre of the tile
+fn quarter_turn(pt: Point2) -> Point2 {
+    pt2(1.0 - pt.y, pt.x)
+}
+
+impl Stroke {
+    fn rotated(&self) -> Stroke {
+        match self {
+            Stroke::Line(start, end) => Stroke::Line(quarter_turn(*start), quarter_turn(*end)),
+            Stroke::Arc {
+                centre,
+                radius,
+                start,
+                end,
+            } => Stroke::Arc {
+                centre: quarter_turn(*centre),
+                radius: *radius,
+                start: start + PI / 2.0,
+                end: end + PI / 2.0,
+            },
+            Stroke::Fill(points) => {
+                Stroke::Fill(points.iter().copied().map(quarter_turn).collect())
+            }
+        }
+    }
+
+    fn scaled(&self, scale: f32, offset: Vec2) -> Stroke {
+        let map = |pt: &Point2| *pt * scale + offset;
+        match self {
+            Stroke::Line(start, end) => Stroke::Line(map(start), map(end)),
+            Stroke::Arc {
+                centre,
+                radius,
+                start,
+                end,
+            } => Stroke::Arc {
+                centre: map(centre),
+                radius: radius * scale,
+                start: *start,
+                end: *end,
+            },
+            Stroke::Fill(points) => Stroke::Fill(points.iter().map(map).collect()),
+        }
+    }
+}
+
+impl Tween for Stroke {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        match (self, other) {
+            (Stroke::Line(a, b), Stroke::Line(c, d)) => Stroke::Line(a.lerp(*c, t), b.lerp(*d, t)),
+            (
+                Stroke::Arc {
+                    centre,
+                    radius,
+                    start,
+                    end,
+                },
+                Stroke::Arc {
+                    centre: other_centre,
+                    radius: other_radius,
+                    start: other_start,
+                    end: other_end,
+                },
+            ) => Stroke::Arc {
+                centre: centre.lerp(*other_centre, t),
+                radius: tween::mix(*radius, *other_radius, t),
+                start: tween::mix(*start, *other_start, t),
+                end: tween::mix(*end, *other_end, t),
+            },
+            (Stroke::Fill(a), Stroke::Fill(b)) if a.len() == b.len() => {
+                Stroke::Fill(a.iter().zip(b).map(|(a, b)| a.lerp(*b, t)).collect())
+            }
+            _ => tween::swap(self, other, t),
+        }
+    }
+
+    fn collapsed(&self) -> Self {
+        match self {
+            Stroke::Line(start, end) => {
+                let mid = (*start + *end) / 2.0;
+                Stroke::Line(mid, mid)
+            }
+            Stroke::Arc {
+                centre,
+                radius,
+                start,
+                end,
+            } => Stroke::Arc {
+                centre: *centre,
+                radius: *radius,
+                start: (start + end) / 2.0,
+                end: (start + end) / 2.0,
+            },
+            Stroke::Fill(points) => {
+                let centroid = points.iter().sum::<Vec2>() / points.len().max(1) as f32;
+                Stroke::Fill(vec![centroid; points.len()])
+            }
+        }
+    }
+}
+
+impl Tween for Placement {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Placement {
+            cell: self.cell.lerp(&other.cell, t),
+            tile: Tile(tween::lerp_all(&self.tile.0, &other.tile.0, t)),
+            fill: match (self.fill, other.fill) {
+                (Some(a), Some(b)) => Some(rgba(
+                    tween::mix(a.red, b.red, t),
+                    tween::mix(a.green, b.green, t),
+                    tween::mix(a.blue, b.blue, t),
+                    tween::mix(a.alpha, b.alpha, t),
+                )),
+                _ if t < 0.5 => self.fill,
+                _ => other.fill,
+            },
+        }
+    }
+
+    fn collapsed(&self) -> Self {
+        Placement {
+            cell: self.cell,
+            tile: Tile(self.tile.0.iter().map(Stroke::collapsed).collect()),
+            fill: self.fill,
+        }
+    }
+}
+
+impl Tile {
+    pub fn rotated(&self, quarter_turns: usize) -> Tile {
+        let mut ret = self.clone();
+        for _ in 0..quarter_turns % 4 {
+            ret.0 = ret.0.iter().map(Stroke::rotated).collect();
+        }
+        ret
+    }
+
+    // shrinks four tiles into the quadrants of one, anticlockwise from the bottom left
+    pub fn quartered(tiles: [&Tile; 4]) -> Tile {
+        let offsets = [
+            vec2(0.0, 0.0),
+            vec2(0.5, 0.0),
+            vec2(0.5, 0.5),
+            vec2(0.0, 0.5),
+        ];
+        Tile(
+            tiles
+                .iter()
+                .zip(offsets)
+                .flat_map(|(tile, offset)| tile.0.iter().map(move |s| s.scaled(0.5, offset)))
+                .collect(),
+        )
+    }
+}
+
+impl TileSet {
+    // each prototype contributes its first `rotations` quarter turns to the set
+    pub fn new(prototypes: &[Tile], rotations: usize) -> Self {
+        TileSet(
+            prototypes
+                .iter()
+                .flat_map(|tile| (0..rotations).map(|turns| tile.rotated(turns)))
+                .collect(),
+        )
+    }
+
+    pub fn diagonals() -> Self {
+        let diagonal = Tile(vec![Stroke::Line(pt2(0.0, 0.0), pt2(1.0, 1.0))]);
+        Self::new(&[diagonal], 2)
+    }
+
+    pub fn smith() -> Self {
+        Self::new(&[smith_tile()], 2)
+    }
+
+    pub fn triangles() -> Self {
+        let triangle = Tile(vec![Stroke::Fill(vec![
+            pt2(0.0, 0.0),
+            pt2(1.0, 0.0),
+            pt2(0.0, 1.0),
+        ])]);
+        Self::new(&[triangle], 4)
+    }
+
+    // smith tiles mixed with tiles made from four half-scale smith tiles
+    pub fn multi_scale() -> Self {
+        let a = smith_tile();
+        let b = a.rotated(1);
+        let mut ret = Self::smith();
+        ret.0
+            .extend(Self::new(&[Tile::quartered([&a, &b, &a, &b])], 2).0);
+        ret.0
+            .extend(Self::new(&[Tile::quartered([&a, &a, &b, &b])], 4).0);
+        ret
+    }
+
+    pub fn choose(&self, rng: &mut impl Rng) -> &Tile {
+        &self.0[rng.gen_range(0..self.0.len())]
+    }
+}
+
+impl std::str::FromStr for TileSet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "diagonals" => Ok(Self::diagonals()),
+            "smith" => Ok(Self::smith()),
+            "triangles" => Ok(Self::triangles()),
+            "multi-scale" => Ok(Self::multi_scale()),
+            _ => Err("expected one of diagonals, smith, triangles, multi-scale".to_owned()),
+        }
+    }
+}
+
+// quarter circles about two opposite corners
+fn smith_tile() -> Tile {
+    Tile(vec![
+        Stroke::Arc {
+            centre: pt2(0.0, 0.0),
+            radius: 0.5,
+            start: 0.0,
+            end: PI / 2.0,
+        },
+        Stroke::Arc {
+            centre: pt2(1.0, 1.0),
+            radius: 0.5,
+            start: PI,
+            end: PI * 1.5,
+        },
+    ])
+}
+
+// places a random tile in each cell, with its filled strokes coloured from `fills` when given
+pub fn place(tiles: &TileSet, cells: &[Cell], fills: Option<&Fills>) -> Vec<Placement> {
+    let mut rng = seed::rng();
+    cells
+        .iter()
+        .map(|&cell| Placement {
+            cell,
+            tile: tiles.choose(&mut rng).clone(),
+            fill: fills.map(|fills| fills.sample(&mut rng)),
+        })
+        .collect()
+}
+
+pub fn draw_placement(placement: &Placement, canvas: &mut dyn Canvas, weight: f32, color: Rgba) {
+    let map = |pt: Point2| placement.cell.origin + pt * placement.cell.size;
+    for stroke in &placement.tile.0 {
+        match stroke {
+            Stroke::Line(start, end) => {
+                canvas.line(map(*start), map(*end), weight, Caps::Square, color);
+            }
+            Stroke::Arc {
+                centre,
+                radius,
+                start,
+                end,
+            } => {
+                let points: Vec<Point2> = (0..=ARC_RESOLUTION)
+                    .map(|k| {
+                        let angle = start + (end - start) * k as f32 / ARC_RESOLUTION as f32;
+                        map(*centre + vec2(angle.cos(), angle.sin()) * *radius)
+                    })
+                    .collect();
+                canvas.polyline(&points, weight, color);
+            }
+            Stroke::Fill(points) => {
+                let points: Vec<Point2> = points.iter().copied().map(map).collect();
+                canvas.polygon(&points, Some(placement.fill.unwrap_or(color)), None);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotating_a_diagonal_reverses_it() {
+        let diagonals = TileSet::diagonals();
+        assert_eq!(
+            diagonals.0[1],
+            Tile(vec![Stroke::Line(pt2(1.0, 0.0), pt2(0.0, 1.0))])
+        );
+    }
+
+    #[test]
+    fn four_quarter_turns_are_the_identity() {
+        let triangles = TileSet::triangles();
+        let triangle = &triangles.0[0];
+        assert_eq!(triangle.rotated(4), *triangle);
+        assert_eq!(triangle.rotated(1).rotated(3), *triangle);
+    }
+
+    #[test]
+    fn quartered_tiles_stay_in_unit_space() {
+        let triangles = TileSet::triangles();
+        let t = &triangles.0[0];
+        let Stroke::Fill(points) = &Tile::quartered([t, t, t, t]).0[2] else {
+            panic!();
+        };
+        assert_eq!(points, &vec![pt2(0.5, 0.5), pt2(1.0, 0.5), pt2(0.5, 1.0)]);
+    }
+}
@@ -0,0 +1,213 @@
+use crate::params;
+use crate::sketch::Param;
+use nannou::ease;
+
+type EaseFn = fn(f32, f32, f32, f32) -> f32;
+
+// an element of a model which can be interpolated towards its counterpart in the next generation
+pub trait Tween: Clone {
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+    // the element shrunk to nothing, which new elements grow from and removed elements shrink into
+    fn collapsed(&self) -> Self;
+}
+
+// an easing curve in pennereq's (time, begin, change, duration) form
+#[derive(Clone, Copy)]
+pub struct Ease(EaseFn);
+
+// the parameters of tutorials which ease between generations
+pub const PARAMS: &[Param] = &[
+    Param {
+        name: "tween",
+        default: "0",
+        values: "0 or more seconds",
+        description: "the length of the transition to each new generation",
+        parse: |value| params::check_range(value, 0.0_f32..=f32::MAX),
+    },
+    Param {
+        name: "ease",
+        default: "cubic",
+        values:
+            "linear, quad, cubic, sine, expo, back, elastic, bounce, each with -in, -out or -in-out",
+        description: "the curve of the transition",
+        parse: params::check::<Ease>,
+    },
+];
+
+// a model which eases from one generation to the next rather than swapping instantly
+pub struct Tweened<E> {
+    from: Vec<E>,
+    to: Vec<E>,
+    elapsed: f32,
+    duration: f32,
+    ease: Ease,
+}
+
+impl Ease {
+    pub fn at(&self, t: f32) -> f32 {
+        (self.0)(t.clamp(0.0, 1.0), 0.0, 1.0, 1.0)
+    }
+}
+
+fn linear(t: f32, b: f32, c: f32, d: f32) -> f32 {
+    b + c * t / d
+}
+
+// the in, out and in-out variants of each curve
+fn curves(name: &str) -> Option<[EaseFn; 3]> {
+    use ease::*;
+    Some(match name {
+        "linear" => [linear, linear, linear],
+        "quad" => [quad::ease_in, quad::ease_out, quad::ease_in_out],
+        "cubic" => [cubic::ease_in, cubic::ease_out, cubic::ease_in_out],
+        "sine" => [sine::ease_in, sine::ease_out, sine::ease_in_out],
+        "expo" => [expo::ease_in, expo::ease_out, expo::ease_in_out],
+        "back" => [back::ease_in, back::ease_out, back::ease_in_out],
+        "elastic" => [elastic::ease_in, elastic::ease_out, elastic::ease_in_out],
+        "bounce" => [bounce::ease_in, bounce::ease_out, bounce::ease_in_out],
+        _ => return None,
+    })
+}
+
+// a curve such as `cubic`, optionally followed by `-in`, `-out` or `-in-out` (the default)
+impl std::str::FromStr for Ease {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, mode) = s.split_once('-').unwrap_or((s, "in-out"));
+        let index = match mode {
+            "in" => Some(0),
+            "out" => Some(1),
+            "in-out" => Some(2),
+            _ => None,
+        };
+        match (curves(name), index) {
+            (Some(curves), Some(index)) => Ok(Ease(curves[index])),
+            _ => Err(
+                "expected one of linear, quad, cubic, sine, expo, back, elastic, bounce, \
+                 optionally followed by -in, -out or -in-out"
+                    .to_owned(),
+            ),
+        }
+    }
+}
+
+pub fn mix(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+// matches elements by index, growing or shrinking any which have no counterpart
+pub fn lerp_all<E: Tween>(from: &[E], to: &[E], t: f32) -> Vec<E> {
+    (0..from.len().max(to.len()))
+        .filter_map(|i| match (from.get(i), to.get(i)) {
+            (Some(a), Some(b)) => Some(a.lerp(b, t)),
+            (Some(a), None) => Some(a.lerp(&a.collapsed(), t)),
+            (None, Some(b)) => Some(b.collapsed().lerp(b, t)),
+            (None, None) => None,
+        })
+        .collect()
+}
+
+// for elements which cannot be interpolated directly, shrinks one before growing the other
+pub fn swap<E: Tween>(from: &E, to: &E, t: f32) -> E {
+    if t < 0.5 {
+        from.lerp(&from.collapsed(), t * 2.0)
+    } else {
+        to.collapsed().lerp(to, t * 2.0 - 1.0)
+    }
+}
+
+impl<E: Tween> Tweened<E> {
+    // reads the transition's length in seconds from `tween`, and its curve from `ease`
+    pub fn new(elements: Vec<E>) -> Self {
+        let duration = params::get("tween", 0.0);
+        Tweened {
+            from: Vec::new(),
+            to: elements,
+            elapsed: duration,
+            duration,
+            ease: params::get("ease", Ease(ease::cubic::ease_in_out)),
+        }
+    }
+
+    // starts a transition from wherever the current one has got to
+    pub fn retarget(&mut self, elements: Vec<E>) {
+        self.from = self.current();
+        self.to = elements;
+        self.elapsed = 0.0;
+    }
+
+    // changes every element, such as to fit a resized window, without interrupting the transition
+    pub fn map(&mut self, f: impl Fn(&E) -> E) {
+        self.from = self.from.iter().map(&f).collect();
+        self.to = self.to.iter().map(&f).collect();
+    }
+
+    pub fn advance(&mut self, seconds: f32) {
+        self.elapsed += seconds;
+        if self.finished() {
+            self.from.clear();
+        }
+    }
+
+    pub fn current(&self) -> Vec<E> {
+        if self.finished() {
+            return self.to.clone();
+        }
+        let t = self.ease.at(self.elapsed / self.duration);
+        lerp_all(&self.from, &self.to, t)
+    }
+
+    fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Dot(f32);
+
+    impl Tween for Dot {
+        fn lerp(&self, other: &Self, t: f32) -> Self {
+            Dot(mix(self.0, other.0, t))
+        }
+
+        fn collapsed(&self) -> Self {
+            Dot(0.0)
+        }
+    }
+
+    #[test]
+    fn eases_start_and_end_in_place() {
+        for name in ["linear", "cubic-in", "sine-out", "bounce-in-out"] {
+            let ease: Ease = name.parse().unwrap();
+            assert!(ease.at(0.0).abs() < 1e-6, "{name}");
+            assert!((ease.at(1.0) - 1.0).abs() < 1e-6, "{name}");
+        }
+        assert!("cubic-sideways".parse::<Ease>().is_err());
+    }
+
+    #[test]
+    fn unmatched_elements_grow_and_shrink() {
+        let from = [Dot(1.0), Dot(2.0)];
+        let to = [Dot(3.0)];
+        assert_eq!(lerp_all(&from, &to, 0.5), vec![Dot(2.0), Dot(1.0)]);
+        assert_eq!(lerp_all(&to, &from, 0.25), vec![Dot(2.5), Dot(0.5)]);
+    }
+
+    #[test]
+    fn mapping_keeps_the_transition() {
+        let mut dots = Tweened {
+            from: vec![Dot(1.0)],
+            to: vec![Dot(3.0)],
+            elapsed: 0.5,
+            duration: 1.0,
+            ease: "linear".parse().unwrap(),
+        };
+        dots.map(|dot| Dot(dot.0 * 2.0));
+        assert_eq!(dots.current(), vec![Dot(4.0)]);
+    }
+}
@@ -1,28 +1,45 @@
+use crate::canvas::{Canvas, Caps};
 use crate::common;
-use itertools::Itertools;
+use crate::layout;
+use crate::palette;
+use crate::seed;
+use crate::sketch::{Param, Sketch};
 use nannou::prelude::*;
 use rand::Rng;
 
-type Line = (Point2, Point2);
+type Line = (Point2, Point2, f32);
 type Model = Vec<Line>;
 
-pub fn run() {
-    nannou::app(generate_model)
-        .event(|a, m, e| common::refresh_model_on_space(a, m, e, generate_model))
-        .simple_window(view)
-        .size(800, 800)
-        .run();
+pub struct UnDeuxTrois;
+
+impl Sketch for UnDeuxTrois {
+    type Model = Model;
+
+    const NAME: &'static str = "un-deux-trois";
+    const DESCRIPTION: &'static str = "one, two and then three turned lines in each cell";
+
+    fn params() -> Vec<Param> {
+        layout::PARAMS.to_vec()
+    }
+
+    fn generate(size: Vec2) -> Self::Model {
+        generate_model(size)
+    }
+
+    fn draw(model: &Self::Model, canvas: &mut dyn Canvas) {
+        draw_model(model, canvas);
+    }
 }
 
-fn generate_model(app: &App) -> Model {
+fn generate_model(size: Vec2) -> Model {
     let n = 15;
-    let width = app.main_window().inner_size_points().0 * 0.9;
-    let step = width / (n as f32);
-    let start = -width / 2.0;
-    let mut _rng = rand::thread_rng();
-    let mut rng = rand::thread_rng();
+    let extent = size * 0.9;
+    let start = -extent / 2.0;
+    let mut rng = seed::rng();
     let mut ret = Vec::new();
-    for (i, j) in (0..n).cartesian_product(0..n) {
+    for cell in layout::cells(start, extent, n) {
+        // the rows fall into three bands from the bottom, however many there are
+        let j = (cell.fraction(start, extent).y * (n - 1) as f32).round() as usize;
         let nlines = 3 - j / 5;
         let (line_start, line_step) = match nlines {
             1 => (0.5, 0.5),
@@ -30,37 +47,24 @@ fn generate_model(app: &App) -> Model {
             3 => (0.1, 0.4),
             _ => unreachable!(),
         };
-        let i = i as f32;
-        let j = j as f32;
-        let mid_point = pt2(start + step * (i + 0.5), start + step * (j + 0.5));
+        let mid_point = cell.centre();
         let angle = rng.gen_range(0.0..std::f32::consts::PI);
         for k in 0..nlines {
-            let k = k as f32;
-            let mut p1 = pt2(
-                start + step * (i + line_step * k + line_start),
-                start + step * j,
-            );
-            let mut p2 = pt2(
-                start + step * (i + line_step * k + line_start),
-                start + step * (j + 1.0),
-            );
+            let x = cell.origin.x + cell.size * (line_step * k as f32 + line_start);
+            let mut p1 = pt2(x, cell.origin.y);
+            let mut p2 = pt2(x, cell.origin.y + cell.size);
             common::rotate_about_point(&mut p1, &mid_point, angle);
             common::rotate_about_point(&mut p2, &mid_point, angle);
-            ret.push((p1, p2));
+            ret.push((p1, p2, common::scaled(5.0, size) * cell.scale));
         }
     }
     ret
 }
 
-fn view(app: &App, model: &Model, frame: Frame) {
-    let draw = app.draw();
-    draw.background().color(WHITE);
+fn draw_model(model: &Model, canvas: &mut dyn Canvas) {
+    let palette = palette::current();
+    canvas.background(palette.background);
     for line in model {
-        draw.line()
-            .start(line.0)
-            .end(line.1)
-            .caps_round()
-            .stroke_weight(5.0);
+        canvas.line(line.0, line.1, line.2, Caps::Round, palette.foreground);
     }
-    draw.to_frame(app, &frame).unwrap();
 }